@@ -1,9 +1,11 @@
 use std::collections::BinaryHeap;
 use std::cmp::Reverse;
 use crate::algorithms::priority_queue::PriorityQueue;
+use crate::search::cost::{Cost, OrderedFloat};
 
+#[derive(Default)]
 pub struct AStarQueue {
-    heap: BinaryHeap<Reverse<(i32, usize)>>, // (priority, node_index)
+    heap: BinaryHeap<Reverse<(OrderedFloat, usize)>>, // (priority, node_index)
 }
 
 impl AStarQueue {
@@ -14,9 +16,9 @@ impl AStarQueue {
     }
 }
 
-impl PriorityQueue for AStarQueue {
-    fn insert(&mut self, node_index: usize, cost: i32, heuristic: f64) {
-        let priority = (cost as f64 + heuristic) as i32;
+impl<C: Cost> PriorityQueue<C> for AStarQueue {
+    fn insert(&mut self, node_index: usize, cost: C, heuristic: f64) {
+        let priority = OrderedFloat(cost.to_f64() + heuristic);
         self.heap.push(Reverse((priority, node_index)));
     }
 
@@ -1,7 +1,9 @@
 use std::collections::VecDeque;
 use crate::algorithms::priority_queue::PriorityQueue;
+use crate::search::cost::Cost;
 
 // A simple FIFO queue for BFS
+#[derive(Default)]
 pub struct BfsQueue {
     queue: VecDeque<usize>,
 }
@@ -14,8 +16,8 @@ impl BfsQueue {
     }
 }
 
-impl PriorityQueue for BfsQueue {
-    fn insert(&mut self, node_index: usize, cost: i32, heuristic_value: f64) {
+impl<C: Cost> PriorityQueue<C> for BfsQueue {
+    fn insert(&mut self, node_index: usize, _cost: C, _heuristic_value: f64) {
         self.queue.push_back(node_index);
     }
 
@@ -1,5 +1,7 @@
 use crate::algorithms::priority_queue::PriorityQueue;
+use crate::search::cost::Cost;
 
+#[derive(Default)]
 pub struct DfsQueue {
     stack: Vec<usize>,
 }
@@ -12,8 +14,8 @@ impl DfsQueue {
     }
 }
 
-impl PriorityQueue for DfsQueue {
-    fn insert(&mut self, node_index: usize, cost: i32, heuristic_value: f64) {
+impl<C: Cost> PriorityQueue<C> for DfsQueue {
+    fn insert(&mut self, node_index: usize, _cost: C, _heuristic_value: f64) {
         self.stack.push(node_index);
     }
 
@@ -1,7 +1,9 @@
 use std::collections::BinaryHeap;
 use std::cmp::Reverse;
 use crate::algorithms::priority_queue::PriorityQueue;
+use crate::search::cost::Cost;
 
+#[derive(Default)]
 pub struct GBFSQueue {
     heap: BinaryHeap<Reverse<(i32, usize)>>, // (priority, node_index)
 }
@@ -14,8 +16,8 @@ impl crate::algorithms::gbfs::GBFSQueue {
     }
 }
 
-impl PriorityQueue for crate::algorithms::gbfs::GBFSQueue {
-    fn insert(&mut self, node_index: usize, cost: i32, heuristic: f64) {
+impl<C: Cost> PriorityQueue<C> for crate::algorithms::gbfs::GBFSQueue {
+    fn insert(&mut self, node_index: usize, _cost: C, heuristic: f64) {
         let priority = heuristic as i32;
         self.heap.push(Reverse((priority, node_index)));
     }
@@ -1,5 +1,6 @@
-pub(crate) mod priority_queue;
-pub(crate) mod bfs;
-mod dfs;
-pub(crate) mod astar;
-pub(crate) mod gbfs;
\ No newline at end of file
+pub mod priority_queue;
+pub mod bfs;
+pub mod dfs;
+pub mod astar;
+pub mod gbfs;
+pub mod weighted_astar;
\ No newline at end of file
@@ -1,7 +1,18 @@
-use std::cmp::Ordering;
+use crate::search::cost::Cost;
 
 // Define a trait for the priority queue to be used in the generic search
-pub trait PriorityQueue {
-    fn insert(&mut self, node_index: usize, cost: i32, heuristic_value: f64); // Insert a node with its cost or priority
+pub trait PriorityQueue<C: Cost = i32> {
+    fn insert(&mut self, node_index: usize, cost: C, heuristic_value: f64); // Insert a node with its cost or priority
     fn pop(&mut self) -> Option<usize>; // Pop the next node based on the queue’s ordering
 }
+
+// Lets the strategy be chosen at runtime, as `Box<dyn PriorityQueue<C>>`
+impl<C: Cost, Q: PriorityQueue<C> + ?Sized> PriorityQueue<C> for Box<Q> {
+    fn insert(&mut self, node_index: usize, cost: C, heuristic_value: f64) {
+        (**self).insert(node_index, cost, heuristic_value)
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        (**self).pop()
+    }
+}
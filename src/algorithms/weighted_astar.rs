@@ -0,0 +1,52 @@
+use std::collections::BinaryHeap;
+use std::cmp::Reverse;
+use crate::algorithms::priority_queue::PriorityQueue;
+use crate::search::cost::{Cost, OrderedFloat};
+
+// Order among nodes with the same priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreaking {
+    #[default]
+    Fifo,  // First inserted first, as `AStarQueue`
+    HighG, // Deepest (highest path cost) first, usually fewer expansions near the goal
+    LowG,  // Shallowest first
+}
+
+// A* with the heuristic inflated by `weight`: with an admissible heuristic the solution costs at
+// most `weight` times the optimum, usually with far fewer expansions
+pub struct WeightedAStarQueue {
+    weight: f64,
+    tie_breaking: TieBreaking,
+    heap: BinaryHeap<Reverse<(OrderedFloat, OrderedFloat, usize)>>, // (priority, tie breaker, node_index)
+}
+
+impl WeightedAStarQueue {
+    pub fn new(weight: f64) -> Self {
+        WeightedAStarQueue {
+            weight,
+            tie_breaking: TieBreaking::Fifo,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    pub fn with_tie_breaking(mut self, tie_breaking: TieBreaking) -> Self {
+        self.tie_breaking = tie_breaking;
+        self
+    }
+}
+
+impl<C: Cost> PriorityQueue<C> for WeightedAStarQueue {
+    fn insert(&mut self, node_index: usize, cost: C, heuristic: f64) {
+        let priority = OrderedFloat(cost.to_f64() + self.weight * heuristic);
+        let tie_breaker = match self.tie_breaking {
+            TieBreaking::Fifo => 0.0,
+            TieBreaking::HighG => -cost.to_f64(),
+            TieBreaking::LowG => cost.to_f64(),
+        };
+        self.heap.push(Reverse((priority, OrderedFloat(tie_breaker), node_index)));
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.heap.pop().map(|Reverse((_, _, index))| index)
+    }
+}
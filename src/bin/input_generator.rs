@@ -0,0 +1,40 @@
+use black_box_planning_solver::problems::taxi_problem::random_input_generator::generate_input;
+use clap::Parser;
+use std::fs::File;
+use std::io::Write;
+
+#[derive(Parser)]
+struct Args {
+    #[arg(short, long, help = "Width of the map")]
+    width: i32,
+
+    #[arg(long, help = "Height of the map")]
+    height: i32,
+
+    #[arg(short, long, help = "Number of passengers")]
+    passengers: usize,
+
+    #[arg(short, long, help = "Number of impassable tiles")]
+    impassable: usize,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    match generate_input(args.width, args.height, args.passengers, args.impassable) {
+        Ok(json_data) => {
+            if let Err(e) = write_to_file(json_data) {
+                eprintln!("Error writing file: {}", e);
+            } else {
+                println!("Input JSON generated successfully: generated_input.json");
+            }
+        }
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+fn write_to_file(json_data: String) -> std::io::Result<()> {
+    let mut file = File::create("inputs/taxi_problem/generated_input.json")?;
+    write!(file, "{}", json_data)?;
+    Ok(())
+}
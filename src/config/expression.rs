@@ -0,0 +1,208 @@
+use std::fmt;
+
+// Parsed configuration expression, e.g. `astar(h=max(taxi, goalcount), w=1.5)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigExpr {
+    Number(f64),
+    Bool(bool),
+    Call(Call), // Also plain names such as `taxi`, as a call without arguments
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub name: String,
+    pub positional: Vec<ConfigExpr>,
+    pub named: Vec<(String, ConfigExpr)>, // In order of appearance, keys are unique
+}
+
+impl Call {
+    pub fn has_arguments(&self) -> bool {
+        !self.positional.is_empty() || !self.named.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ConfigError { message: message.into() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Grammar:
+//     expr  := number | name [ "(" [ arg { "," arg } ] ")" ]
+//     arg   := name "=" expr | expr
+// `true` and `false` are booleans; whitespace is ignored between tokens
+pub fn parse_config(input: &str) -> Result<ConfigExpr, ConfigError> {
+    let mut parser = Parser { input: input.as_bytes(), position: 0 };
+    let expr = parser.expr()?;
+    parser.skip_whitespace();
+    if parser.position < parser.input.len() {
+        return Err(parser.error("unexpected trailing input"));
+    }
+    Ok(expr)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ConfigError {
+        ConfigError::new(format!("{} at column {}", message, self.position + 1))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.input.get(self.position).is_some_and(|c| c.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.position).copied()
+    }
+
+    fn eat(&mut self, expected: u8) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &str {
+        let start = self.position;
+        while self.input.get(self.position).is_some_and(|c| predicate(*c)) {
+            self.position += 1;
+        }
+        // Only ASCII bytes are accepted by the predicates
+        std::str::from_utf8(&self.input[start..self.position]).unwrap()
+    }
+
+    fn name(&mut self) -> Result<String, ConfigError> {
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {
+                Ok(self.take_while(|c| c.is_ascii_alphanumeric() || c == b'_').to_string())
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn expr(&mut self) -> Result<ConfigExpr, ConfigError> {
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == b'-' || c == b'.' => {
+                let start = self.position;
+                let text = self.take_while(|c| c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E'));
+                match text.parse::<f64>() {
+                    Ok(number) => Ok(ConfigExpr::Number(number)),
+                    Err(_) => {
+                        self.position = start;
+                        Err(self.error("invalid number"))
+                    }
+                }
+            }
+            _ => {
+                let name = self.name()?;
+                if !self.eat(b'(') {
+                    return Ok(match name.as_str() {
+                        "true" => ConfigExpr::Bool(true),
+                        "false" => ConfigExpr::Bool(false),
+                        _ => ConfigExpr::Call(Call { name, positional: Vec::new(), named: Vec::new() }),
+                    });
+                }
+                let mut call = Call { name, positional: Vec::new(), named: Vec::new() };
+                if self.eat(b')') {
+                    return Ok(ConfigExpr::Call(call));
+                }
+                loop {
+                    self.argument(&mut call)?;
+                    if self.eat(b')') {
+                        return Ok(ConfigExpr::Call(call));
+                    }
+                    if !self.eat(b',') {
+                        return Err(self.error("expected ',' or ')'"));
+                    }
+                }
+            }
+        }
+    }
+
+    fn argument(&mut self, call: &mut Call) -> Result<(), ConfigError> {
+        // A name followed by '=' is a keyword argument, anything else a positional one
+        self.skip_whitespace();
+        let start = self.position;
+        if let Ok(key) = self.name() {
+            if self.eat(b'=') {
+                if call.named.iter().any(|(existing, _)| *existing == key) {
+                    self.position = start;
+                    return Err(self.error(&format!("duplicate argument '{}'", key)));
+                }
+                let value = self.expr()?;
+                call.named.push((key, value));
+                return Ok(());
+            }
+        }
+        self.position = start;
+        let value = self.expr()?;
+        call.positional.push(value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigExpr::Number(number) => write!(f, "{}", number),
+            ConfigExpr::Bool(value) => write!(f, "{}", value),
+            ConfigExpr::Call(call) => {
+                write!(f, "{}", call.name)?;
+                if !call.has_arguments() {
+                    return Ok(());
+                }
+                let positional = call.positional.iter().map(|value| value.to_string());
+                let named = call.named.iter().map(|(key, value)| format!("{}={}", key, value));
+                write!(f, "({})", positional.chain(named).collect::<Vec<_>>().join(", "))
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested_expression() {
+        let expr = parse_config("gbfs( h = max(taxi, goalcount), lazy=true, w=1.5 )").unwrap();
+
+        let ConfigExpr::Call(call) = &expr else { panic!("expected a call") };
+        assert_eq!(call.name, "gbfs");
+        assert_eq!(call.named[1], ("lazy".to_string(), ConfigExpr::Bool(true)));
+        assert_eq!(call.named[2], ("w".to_string(), ConfigExpr::Number(1.5)));
+        assert_eq!(expr.to_string(), "gbfs(h=max(taxi, goalcount), lazy=true, w=1.5)");
+        assert_eq!(parse_config(&expr.to_string()).unwrap(), expr);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_config("astar(w=1.5").unwrap_err().message, "expected ',' or ')' at column 12");
+        assert_eq!(parse_config("astar(w=1, w=2)").unwrap_err().message, "duplicate argument 'w' at column 12");
+        assert_eq!(parse_config("astar() x").unwrap_err().message, "unexpected trailing input at column 9");
+        assert!(parse_config("astar(w=1..5)").is_err());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod expression;
+pub mod registry;
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use crate::algorithms::weighted_astar::TieBreaking;
+use crate::config::expression::{parse_config, Call, ConfigError, ConfigExpr};
+use crate::problems::example_problem::SimpleProblem;
+use crate::problems::farm_problem::farm_problem::FarmProblem;
+use crate::problems::problem::Problem;
+use crate::problems::taxi_problem::taxi_problem::TaxiProblem;
+use crate::search::solver::{Algorithm, Heuristic, SearchConfiguration};
+
+// Settings chosen by an algorithm entry from its arguments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlgorithmSettings {
+    pub algorithm: Algorithm,
+    pub tie_breaking: TieBreaking,
+    pub lazy_heuristic: bool,
+}
+
+impl AlgorithmSettings {
+    pub fn new(algorithm: Algorithm) -> Self {
+        AlgorithmSettings { algorithm, tie_breaking: TieBreaking::Fifo, lazy_heuristic: false }
+    }
+}
+
+// Builds the settings of a named algorithm; `h` has already been taken by the registry
+pub type AlgorithmFactory = fn(&mut Arguments) -> Result<AlgorithmSettings, ConfigError>;
+
+// Keyword arguments of a call, each of which must be consumed exactly once
+pub struct Arguments<'e> {
+    call: &'e Call,
+    used: Vec<bool>,
+}
+
+impl<'e> Arguments<'e> {
+    fn new(call: &'e Call) -> Self {
+        Arguments { call, used: vec![false; call.named.len()] }
+    }
+
+    pub fn take(&mut self, key: &str) -> Option<&'e ConfigExpr> {
+        let index = self.call.named.iter().position(|(name, _)| name == key)?;
+        self.used[index] = true;
+        Some(&self.call.named[index].1)
+    }
+
+    pub fn number(&mut self, key: &str) -> Result<Option<f64>, ConfigError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(ConfigExpr::Number(number)) => Ok(Some(*number)),
+            Some(other) => Err(ConfigError::new(format!("'{}' must be a number, got '{}'", key, other))),
+        }
+    }
+
+    pub fn bool(&mut self, key: &str) -> Result<Option<bool>, ConfigError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(ConfigExpr::Bool(value)) => Ok(Some(*value)),
+            Some(other) => Err(ConfigError::new(format!("'{}' must be true or false, got '{}'", key, other))),
+        }
+    }
+
+    // A plain name such as `high_g`
+    pub fn name(&mut self, key: &str) -> Result<Option<&'e str>, ConfigError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(ConfigExpr::Call(call)) if !call.has_arguments() => Ok(Some(&call.name)),
+            Some(other) => Err(ConfigError::new(format!("'{}' must be a name, got '{}'", key, other))),
+        }
+    }
+
+    fn finish(self) -> Result<(), ConfigError> {
+        if !self.call.positional.is_empty() {
+            return Err(ConfigError::new(format!("'{}' only takes keyword arguments", self.call.name)));
+        }
+        match self.call.named.iter().zip(&self.used).find(|(_, used)| !**used) {
+            Some(((key, _), _)) => Err(ConfigError::new(format!("unknown argument '{}' for '{}'", key, self.call.name))),
+            None => Ok(()),
+        }
+    }
+}
+
+// Named algorithms and heuristics that configuration strings are resolved against. Heuristics
+// combine with `max(...)`, `min(...)` and `sum(...)`; `h` defaults to the problem's heuristic.
+pub struct Registry<'a, P: Problem> {
+    algorithms: BTreeMap<String, AlgorithmFactory>,
+    heuristics: BTreeMap<String, Heuristic<'a, P>>,
+}
+
+impl<'a, P: Problem + 'a> Registry<'a, P> {
+    // The bundled algorithms plus the `zero` and `default` (`Problem::heuristic`) heuristics
+    pub fn new(problem: &'a P) -> Self {
+        let mut registry = Registry { algorithms: BTreeMap::new(), heuristics: BTreeMap::new() };
+        registry.register_algorithm("bfs", |_| Ok(AlgorithmSettings::new(Algorithm::Bfs)));
+        registry.register_algorithm("dfs", |_| Ok(AlgorithmSettings::new(Algorithm::Dfs)));
+        registry.register_algorithm("gbfs", |arguments| {
+            let mut settings = AlgorithmSettings::new(Algorithm::Gbfs);
+            settings.lazy_heuristic = arguments.bool("lazy")?.unwrap_or(false);
+            Ok(settings)
+        });
+        registry.register_algorithm("astar", |arguments| {
+            let algorithm = match arguments.number("w")? {
+                None | Some(1.0) => Algorithm::AStar,
+                Some(weight) if weight.is_finite() && weight > 0.0 => Algorithm::WeightedAStar(weight),
+                Some(weight) => return Err(ConfigError::new(format!("'w' must be positive and finite, got {}", weight))),
+            };
+            let mut settings = AlgorithmSettings::new(algorithm);
+            settings.tie_breaking = match arguments.name("tiebreak")? {
+                None | Some("fifo") => TieBreaking::Fifo,
+                Some("high_g") => TieBreaking::HighG,
+                Some("low_g") => TieBreaking::LowG,
+                Some(other) => return Err(ConfigError::new(format!("unknown tiebreak '{}', expected fifo, high_g or low_g", other))),
+            };
+            Ok(settings)
+        });
+
+        registry.register_heuristic("zero", |_| 0.0);
+        registry.register_heuristic("default", move |state| problem.heuristic(state));
+        registry
+    }
+
+    // `goalcount`: number of goal conditions unsatisfied according to `Problem::goal_satisfaction`,
+    // if the problem reports it
+    pub fn register_goal_count(&mut self, problem: &'a P) {
+        if problem.goal_satisfaction(&problem.initial_state()).is_some() {
+            self.register_heuristic("goalcount", move |state| {
+                problem.goal_satisfaction(state).map_or(0.0, |satisfaction| satisfaction.unsatisfied() as f64)
+            });
+        }
+    }
+
+    pub fn register_algorithm(&mut self, name: &str, factory: AlgorithmFactory) {
+        self.algorithms.insert(name.to_string(), factory);
+    }
+
+    pub fn register_heuristic(&mut self, name: &str, heuristic: impl Fn(&P::State) -> f64 + 'a) {
+        self.heuristics.insert(name.to_string(), Rc::new(heuristic));
+    }
+
+    pub fn algorithm_names(&self) -> Vec<&str> {
+        self.algorithms.keys().map(String::as_str).collect()
+    }
+
+    pub fn heuristic_names(&self) -> Vec<&str> {
+        self.heuristics.keys().map(String::as_str).collect()
+    }
+
+    pub fn heuristics(&self) -> impl Iterator<Item = (&str, &Heuristic<'a, P>)> {
+        self.heuristics.iter().map(|(name, heuristic)| (name.as_str(), heuristic))
+    }
+
+    // Parse and resolve a configuration string
+    pub fn build(&self, configuration: &str) -> Result<SearchConfiguration<'a, P>, ConfigError> {
+        self.resolve(&parse_config(configuration)?)
+    }
+
+    pub fn resolve(&self, expr: &ConfigExpr) -> Result<SearchConfiguration<'a, P>, ConfigError> {
+        let ConfigExpr::Call(call) = expr else {
+            return Err(ConfigError::new(format!("expected an algorithm, got '{}'", expr)));
+        };
+        let factory = self.algorithms.get(&call.name).ok_or_else(|| {
+            ConfigError::new(format!("unknown algorithm '{}', expected one of: {}", call.name, self.algorithm_names().join(", ")))
+        })?;
+
+        let mut arguments = Arguments::new(call);
+        let heuristic = match arguments.take("h") {
+            Some(expr) => self.resolve_heuristic(expr)?,
+            None => self.heuristics["default"].clone(),
+        };
+        let settings = factory(&mut arguments)?;
+        arguments.finish()?;
+
+        Ok(SearchConfiguration {
+            algorithm: settings.algorithm,
+            tie_breaking: settings.tie_breaking,
+            lazy_heuristic: settings.lazy_heuristic,
+            heuristic,
+        })
+    }
+
+    pub fn resolve_heuristic(&self, expr: &ConfigExpr) -> Result<Heuristic<'a, P>, ConfigError> {
+        let ConfigExpr::Call(call) = expr else {
+            return Err(ConfigError::new(format!("expected a heuristic, got '{}'", expr)));
+        };
+
+        let combine: Option<fn(f64, f64) -> f64> = match call.name.as_str() {
+            "max" => Some(f64::max),
+            "min" => Some(f64::min),
+            "sum" => Some(|a, b| a + b),
+            _ => None,
+        };
+        if let Some(combine) = combine {
+            if call.positional.is_empty() || !call.named.is_empty() {
+                return Err(ConfigError::new(format!("'{}' takes one or more heuristics", call.name)));
+            }
+            let parts = call
+                .positional
+                .iter()
+                .map(|part| self.resolve_heuristic(part))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Rc::new(move |state: &P::State| {
+                parts.iter().map(|part| part(state)).reduce(combine).unwrap()
+            }));
+        }
+
+        let heuristic = self.heuristics.get(&call.name).ok_or_else(|| {
+            ConfigError::new(format!("unknown heuristic '{}', expected one of: {}", call.name, self.heuristic_names().join(", ")))
+        })?;
+        if call.has_arguments() {
+            return Err(ConfigError::new(format!("heuristic '{}' takes no arguments", call.name)));
+        }
+        Ok(heuristic.clone())
+    }
+}
+
+// Registry for the taxi problem: `taxi` (its heuristic) and `goalcount` (undelivered passengers)
+pub fn taxi_registry(problem: &TaxiProblem) -> Registry<'_, TaxiProblem> {
+    let mut registry = Registry::new(problem);
+    registry.register_heuristic("taxi", move |state| problem.heuristic(state));
+    registry.register_goal_count(problem);
+    registry
+}
+
+// Registry for the farm problem: `farm` (its heuristic) and `goalcount`
+pub fn farm_registry(problem: &FarmProblem) -> Registry<'_, FarmProblem> {
+    let mut registry = Registry::new(problem);
+    registry.register_heuristic("farm", move |state| problem.heuristic(state));
+    registry.register_goal_count(problem);
+    registry
+}
+
+// Registry for the example problem: `goalcount` (1 until the health target is reached)
+pub fn simple_registry(problem: &SimpleProblem) -> Registry<'_, SimpleProblem> {
+    let mut registry = Registry::new(problem);
+    registry.register_goal_count(problem);
+    registry
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::taxi_problem::taxi_problem::small_taxi_problem;
+    use crate::search::solver::Solver;
+    use crate::search::validate_plan::validate_plan;
+    use crate::search::state::Position;
+
+    fn create_problem() -> TaxiProblem {
+        let passengers = [(Position::new(3, 0), Position::new(0, 3)), (Position::new(3, 3), Position::new(0, 0))];
+        small_taxi_problem(4, 4, &[], Position::new(0, 0), &passengers)
+    }
+
+    #[test]
+    fn test_build_configurations() {
+        let problem = create_problem();
+        let registry = taxi_registry(&problem);
+        let state = problem.initial_state();
+
+        let configuration = registry.build("astar(h=taxi, w=1.5, tiebreak=high_g)").unwrap();
+        assert_eq!(configuration.algorithm, Algorithm::WeightedAStar(1.5));
+        assert_eq!(configuration.tie_breaking, TieBreaking::HighG);
+        assert_eq!((configuration.heuristic)(&state), problem.heuristic(&state));
+
+        let configuration = registry.build("gbfs(h=max(taxi, goalcount), lazy=true)").unwrap();
+        assert_eq!(configuration.algorithm, Algorithm::Gbfs);
+        assert!(configuration.lazy_heuristic);
+        assert_eq!((configuration.heuristic)(&state), problem.heuristic(&state).max(2.0));
+
+        let configuration = registry.build("bfs").unwrap();
+        assert_eq!(configuration.algorithm, Algorithm::Bfs);
+    }
+
+    #[test]
+    fn test_configured_searches_solve() {
+        let problem = create_problem();
+        let registry = taxi_registry(&problem);
+
+        let optimal = Solver::new(&problem).configure(registry.build("astar(h=goalcount)").unwrap()).solve();
+        assert_eq!(optimal.cost(), Ok(16));
+
+        let lazy = Solver::new(&problem).configure(registry.build("gbfs(h=sum(taxi, goalcount), lazy=true)").unwrap()).solve();
+        for result in [optimal, lazy] {
+            assert!(validate_plan(&problem, &problem.initial_state(), &result.plan.unwrap()).unwrap().is_valid());
+        }
+    }
+
+    #[test]
+    fn test_configuration_errors() {
+        let problem = create_problem();
+        let registry = taxi_registry(&problem);
+        let error = |configuration: &str| registry.build(configuration).err().unwrap().message;
+
+        assert_eq!(error("idastar"), "unknown algorithm 'idastar', expected one of: astar, bfs, dfs, gbfs");
+        assert_eq!(error("astar(h=farm)"), "unknown heuristic 'farm', expected one of: default, goalcount, taxi, zero");
+        assert_eq!(error("astar(lazy=true)"), "unknown argument 'lazy' for 'astar'");
+        assert_eq!(error("astar(w=high_g)"), "'w' must be a number, got 'high_g'");
+        assert_eq!(error("astar(w=0)"), "'w' must be positive and finite, got 0");
+        assert_eq!(error("astar(w=1e999)"), "'w' must be positive and finite, got inf");
+        assert_eq!(error("gbfs(h=max())"), "'max' takes one or more heuristics");
+    }
+}
@@ -0,0 +1,41 @@
+use crate::search::state::Position;
+use crate::problems::problem::Problem;
+use crate::problems::taxi_problem::taxi_problem::{TaxiProblem, TaxiState};
+
+// A model of how the environment changes independently of the agent. It is called after every
+// executed action and may alter both the environment's problem (e.g. passability) and its state.
+pub trait Disturbance<P: Problem> {
+    fn disturb(&mut self, step: usize, problem: &mut P, state: &mut P::State);
+}
+
+impl<P, F> Disturbance<P> for F
+where
+    P: Problem,
+    F: FnMut(usize, &mut P, &mut P::State),
+{
+    fn disturb(&mut self, step: usize, problem: &mut P, state: &mut P::State) {
+        self(step, problem, state)
+    }
+}
+
+// The environment behaves exactly like the model
+pub struct NoDisturbance;
+
+impl<P: Problem> Disturbance<P> for NoDisturbance {
+    fn disturb(&mut self, _step: usize, _problem: &mut P, _state: &mut P::State) {}
+}
+
+// Taxi tiles that become impassable after the given step
+pub struct BlockTaxiTiles {
+    pub schedule: Vec<(usize, Position)>,
+}
+
+impl Disturbance<TaxiProblem> for BlockTaxiTiles {
+    fn disturb(&mut self, step: usize, problem: &mut TaxiProblem, _state: &mut TaxiState) {
+        for (blocked_at, tile) in &self.schedule {
+            if *blocked_at == step {
+                problem.set_impassable(*tile, true);
+            }
+        }
+    }
+}
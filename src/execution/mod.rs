@@ -0,0 +1,3 @@
+pub mod disturbance;
+pub mod trace;
+pub mod simulator;
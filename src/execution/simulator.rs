@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use crate::search::action::SearchAction;
+use crate::search::cost::Cost;
+use crate::search::search::search_problem;
+use crate::problems::problem::Problem;
+use crate::algorithms::priority_queue::PriorityQueue;
+use crate::execution::disturbance::Disturbance;
+use crate::execution::trace::{ExecutionEvent, ExecutionTrace};
+
+// Steps plans through an environment that may be perturbed by a disturbance model.
+// The planner is called on the environment's current problem and state whenever there is no plan,
+// the next action is inapplicable or the observed state diverges from the predicted one.
+// Running fails only if the cost of the executed actions overflows.
+pub struct ExecutionSimulator<P, D, F> {
+    pub problem: P, // The environment; the disturbance may change it between steps
+    disturbance: D,
+    planner: F,
+    pub max_steps: usize,
+    pub max_replans: usize,
+}
+
+impl<P, D, F> ExecutionSimulator<P, D, F>
+where
+    P: Problem,
+    D: Disturbance<P>,
+    F: FnMut(&P, &P::State) -> Result<Vec<P::Action>, &'static str>,
+{
+    pub fn new(problem: P, disturbance: D, planner: F, max_steps: usize, max_replans: usize) -> Self {
+        ExecutionSimulator {
+            problem,
+            disturbance,
+            planner,
+            max_steps,
+            max_replans,
+        }
+    }
+
+    pub fn run(&mut self, initial_state: &P::State) -> Result<ExecutionTrace<P::State, P::Action>, &'static str> {
+        let mut trace = ExecutionTrace::default();
+        let mut state = initial_state.clone();
+        let mut plan: VecDeque<P::Action> = VecDeque::new();
+        let mut planner_calls = 0;
+        let mut step = 0;
+
+        loop {
+            if self.problem.is_goal_state(&state) {
+                trace.events.push(ExecutionEvent::GoalReached { step });
+                trace.reached_goal = true;
+                break;
+            }
+            if step >= self.max_steps {
+                trace.events.push(ExecutionEvent::StepLimitReached { step });
+                break;
+            }
+
+            if plan.is_empty() {
+                if planner_calls > self.max_replans {
+                    trace.events.push(ExecutionEvent::PlanningFailed { step, reason: "Replanning limit reached" });
+                    break;
+                }
+                planner_calls += 1;
+                trace.replans = planner_calls - 1;
+                match (self.planner)(&self.problem, &state) {
+                    Ok(new_plan) if !new_plan.is_empty() => {
+                        trace.events.push(ExecutionEvent::Planned { step, plan: new_plan.clone() });
+                        plan = new_plan.into();
+                    }
+                    Ok(_) => {
+                        trace.events.push(ExecutionEvent::PlanningFailed { step, reason: "Empty plan for a non-goal state" });
+                        break;
+                    }
+                    Err(reason) => {
+                        trace.events.push(ExecutionEvent::PlanningFailed { step, reason });
+                        break;
+                    }
+                }
+            }
+
+            let action = plan.pop_front().unwrap();
+            if !self.problem.is_applicable(&state, &action) {
+                trace.events.push(ExecutionEvent::InapplicableAction { step, action });
+                plan.clear();
+                continue;
+            }
+
+            let expected = self.problem.apply_action(&state, &action);
+            state = expected.clone();
+            step += 1;
+            self.disturbance.disturb(step, &mut self.problem, &mut state);
+
+            trace.total_cost = trace.total_cost.checked_add(action.cost()).ok_or("Path cost overflow")?;
+            trace.executed_actions.push(action.clone());
+            trace.events.push(ExecutionEvent::Executed { step, action, state: state.clone() });
+
+            if state != expected {
+                trace.events.push(ExecutionEvent::Diverged { step, expected, actual: state.clone() });
+                plan.clear();
+            }
+        }
+        Ok(trace)
+    }
+}
+
+// Plan with `generic_search` using the given queue, for use as the simulator's planner
+pub fn search_planner<P, Q>(problem: &P, state: &P::State, queue: Q) -> Result<Vec<P::Action>, &'static str>
+where
+    P: Problem,
+    Q: PriorityQueue<P::Cost>,
+{
+    search_problem(problem, state, queue)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::astar::AStarQueue;
+    use crate::execution::disturbance::{BlockTaxiTiles, NoDisturbance};
+    use crate::search::action::Action;
+    use crate::search::validate_plan::validate_plan;
+    use crate::problems::taxi_problem::taxi_problem::{small_taxi_problem, Passenger, TaxiProblem, TaxiState};
+    use crate::search::state::Position;
+    use std::collections::HashMap;
+
+    fn create_problem() -> (TaxiProblem, TaxiState) {
+        let problem = small_taxi_problem(3, 3, &[], Position::new(0, 0), &[(Position::new(0, 0), Position::new(2, 0))]);
+        let state = problem.initial_state();
+        (problem, state)
+    }
+
+    fn planner(problem: &TaxiProblem, state: &TaxiState) -> Result<Vec<Action>, &'static str> {
+        search_planner(problem, state, AStarQueue::new())
+    }
+
+    #[test]
+    fn test_execution_without_disturbance() {
+        let (problem, state) = create_problem();
+        let mut simulator = ExecutionSimulator::new(problem, NoDisturbance, planner, 50, 5);
+
+        let trace = simulator.run(&state).unwrap();
+
+        assert!(validate_plan(&simulator.problem, &state, &trace.executed_actions).unwrap().is_valid());
+        assert!(trace.reached_goal);
+        assert_eq!(trace.replans, 0);
+        assert_eq!(trace.total_cost, 4);
+    }
+
+    #[test]
+    fn test_replanning_around_new_tiles() {
+        let (problem, state) = create_problem();
+        // Block the whole top row right after the passenger is picked up
+        let disturbance = BlockTaxiTiles {
+            schedule: vec![(1, Position::new(1, 0)), (1, Position::new(1, 1))],
+        };
+        let mut simulator = ExecutionSimulator::new(problem, disturbance, planner, 50, 5);
+
+        let trace = simulator.run(&state).unwrap();
+
+        // The tiles are blocked after the pick-up, so the executed actions never cross them
+        assert!(validate_plan(&simulator.problem, &state, &trace.executed_actions).unwrap().is_valid());
+        assert!(trace.reached_goal);
+        assert_eq!(trace.replans, 1);
+        assert!(trace.events.iter().any(|event| matches!(event, ExecutionEvent::InapplicableAction { .. })));
+        assert_eq!(trace.total_cost, 8);
+    }
+
+    #[test]
+    fn test_divergence_triggers_replanning() {
+        let (problem, state) = create_problem();
+        // The passenger jumps out of the taxi once, right after the first move
+        let mut jumped = false;
+        let disturbance = move |_step: usize, _problem: &mut TaxiProblem, state: &mut TaxiState| {
+            if !jumped && state.passengers[0].in_taxi && state.taxi != Position::new(0, 0) {
+                state.passengers[0] = Passenger { position: state.taxi, in_taxi: false };
+                jumped = true;
+            }
+        };
+        let mut simulator = ExecutionSimulator::new(problem, disturbance, planner, 50, 5);
+
+        let trace = simulator.run(&state).unwrap();
+
+        assert!(trace.reached_goal);
+        assert_eq!(trace.replans, 1);
+        assert!(trace.events.iter().any(|event| matches!(event, ExecutionEvent::Diverged { step: 2, .. })));
+
+        // The replay would not diverge, so the new plan is checked from the diverged state instead
+        let diverged = trace.events.iter().find_map(|event| match event {
+            ExecutionEvent::Diverged { actual, .. } => Some(actual),
+            _ => None,
+        });
+        let replan = trace.events.iter().rev().find_map(|event| match event {
+            ExecutionEvent::Planned { plan, .. } => Some(plan),
+            _ => None,
+        });
+        assert!(validate_plan(&simulator.problem, diverged.unwrap(), replan.unwrap()).unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_cost_overflow_is_an_error() {
+        let (problem, state) = create_problem();
+        let expensive = |_: &TaxiProblem, _: &TaxiState| {
+            Ok(vec![Action::new("move_right".to_string(), i32::MAX / 2 + 1, HashMap::new()); 2])
+        };
+        let mut simulator = ExecutionSimulator::new(problem, NoDisturbance, expensive, 50, 5);
+
+        assert_eq!(simulator.run(&state).unwrap_err(), "Path cost overflow");
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::Debug;
+use crate::search::{state::State, action::{Action, SearchAction}, cost::Cost};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionEvent<S = State, A = Action> {
+    Planned { step: usize, plan: Vec<A> },
+    PlanningFailed { step: usize, reason: &'static str },
+    Executed { step: usize, action: A, state: S },
+    // The environment state after an action differs from the state the model predicted
+    Diverged { step: usize, expected: S, actual: S },
+    // The next planned action cannot be applied in the current environment state
+    InapplicableAction { step: usize, action: A },
+    GoalReached { step: usize },
+    StepLimitReached { step: usize },
+}
+
+// Everything that happened while executing plans in the environment
+#[derive(Debug, Clone)]
+pub struct ExecutionTrace<S = State, A: SearchAction = Action> {
+    pub events: Vec<ExecutionEvent<S, A>>,
+    pub executed_actions: Vec<A>,
+    pub total_cost: A::Cost,
+    pub replans: usize,
+    pub reached_goal: bool,
+}
+
+impl<S, A: SearchAction> Default for ExecutionTrace<S, A> {
+    fn default() -> Self {
+        ExecutionTrace {
+            events: Vec::new(),
+            executed_actions: Vec::new(),
+            total_cost: A::Cost::zero(),
+            replans: 0,
+            reached_goal: false,
+        }
+    }
+}
+
+impl<S: Debug, A: SearchAction> ExecutionTrace<S, A> {
+    pub fn print(&self) {
+        for event in &self.events {
+            match event {
+                ExecutionEvent::Planned { step, plan } => {
+                    println!("[{}] Planned {} actions", step, plan.len())
+                }
+                ExecutionEvent::PlanningFailed { step, reason } => {
+                    println!("[{}] Planning failed: {}", step, reason)
+                }
+                ExecutionEvent::Executed { step, action, state } => {
+                    println!("[{}] Executed {}, State: {:?}", step, action.name(), state)
+                }
+                ExecutionEvent::Diverged { step, expected, actual } => {
+                    println!("[{}] Diverged, Expected: {:?}, Actual: {:?}", step, expected, actual)
+                }
+                ExecutionEvent::InapplicableAction { step, action } => {
+                    println!("[{}] Action {} is not applicable", step, action.name())
+                }
+                ExecutionEvent::GoalReached { step } => println!("[{}] Goal reached", step),
+                ExecutionEvent::StepLimitReached { step } => println!("[{}] Step limit reached", step),
+            }
+        }
+        println!(
+            "Executed {} actions with total cost {:?} and {} replans",
+            self.executed_actions.len(),
+            self.total_cost,
+            self.replans
+        );
+    }
+}
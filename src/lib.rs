@@ -0,0 +1,32 @@
+// Black-box planning: problems implement `Problem` and are solved by the generic searches, the
+// Monte Carlo searches, the MDP solvers or the real-time searches. The re-exports below are the
+// stable entry points; the modules stay public for everything else.
+pub mod search;
+pub mod problems;
+pub mod algorithms;
+pub mod mcts;
+pub mod mdp;
+pub mod realtime;
+pub mod execution;
+pub mod config;
+
+pub use crate::problems::problem::Problem;
+pub use crate::problems::stochastic_problem::StochasticProblem;
+pub use crate::search::state::{Position, State, StateError, Value, ValueKind};
+pub use crate::search::action::{Action, SearchAction};
+pub use crate::search::cost::{Cost, OrderedFloat};
+pub use crate::search::goal::{Goal, GoalCondition};
+pub use crate::search::schema::{CompactState, SchemaError, StateSchema};
+pub use crate::search::search::{generic_search, generic_search_with_statistics, search_problem, SearchOptions, SearchStatistics};
+pub use crate::search::solver::{Algorithm, Heuristic, SearchConfiguration, SolveResult, Solver};
+pub use crate::config::expression::{parse_config, ConfigError, ConfigExpr};
+pub use crate::config::registry::Registry;
+pub use crate::search::search_tree::SearchTree;
+pub use crate::search::validate_plan::{validate_plan, PlanValidation};
+pub use crate::algorithms::priority_queue::PriorityQueue;
+pub use crate::algorithms::{astar::AStarQueue, bfs::BfsQueue, dfs::DfsQueue, gbfs::GBFSQueue, weighted_astar::{TieBreaking, WeightedAStarQueue}};
+pub use crate::problems::taxi_problem::taxi_problem::{TaxiProblem, TaxiState};
+pub use crate::problems::taxi_problem::stochastic_taxi_problem::StochasticTaxiProblem;
+pub use crate::problems::farm_problem::farm_problem::FarmProblem;
+pub use crate::problems::dyn_problem::{BoxedProblem, DynProblem, DynState};
+pub use crate::problems::registry::ProblemRegistry;
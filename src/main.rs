@@ -1,96 +1,315 @@
-#![allow(unused_variables, dead_code, unused_imports)]
-mod search;
-mod problems;
-mod algorithms;
-mod mcts;
-
-use crate::search::search_tree;
-use crate::search::state::{Value, State};
-use crate::problems::example_problem::SimpleProblem;
-use crate::algorithms::bfs::BfsQueue;
-use crate::algorithms::astar::AStarQueue;
-use crate::algorithms::gbfs::GBFSQueue;
-use crate::problems::problem::Problem;
-use crate::search::search_tree::SearchTree;
-use crate::search::search::generic_search;
-use crate::problems::taxi_problem::taxi_problem::{load_state_from_json, TaxiProblem};
-use crate::problems::farm_problem::farm_problem::FarmProblem;
-use std::time::Instant;
-
-
-
-fn solve_taxi_problem(){
-    let (state, config) = load_state_from_json("inputs/taxi_problem/generated_input.json").expect("Failed to load state from JSON");
-
-    let problem = TaxiProblem {
-        width: config.width,
-        height: config.height,
-        impassable_tiles: config.impassable_tiles,
-        goals: config.goals,
-    };
+use black_box_planning_solver::problems::taxi_problem::random_input_generator::generate_input;
+use black_box_planning_solver::search::plan_file::{
+    format_action, plan_states, plan_to_json, read_plan, read_plan_json, resolve_plan, write_plan, write_plan_csv, write_plan_ipc,
+};
+use black_box_planning_solver::{
+    parse_config, validate_plan, Action, BoxedProblem, Problem, ProblemRegistry, Registry, SolveResult, Solver,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use std::error::Error;
+use std::process::ExitCode;
+use std::time::Duration;
 
-    let initial_node = problem.create_initial_node(state.clone());
-    let actions = problem.get_possible_actions(&initial_node.state);
-    let mut tree = SearchTree::new(state.clone());
-    let a_star_queue = AStarQueue::new();
-    let gbfs_queue = GBFSQueue::new();
-
-    match generic_search(
-        &mut tree, // Pass mutable reference to tree
-        |state| problem.get_possible_actions(state),
-        |state, action| problem.apply_action(state, action),
-        |state| problem.is_goal_state(state),
-        gbfs_queue,
-        |state| problem.heuristic(state),
-    ) {
-        Ok(actions) => {
-            let total_cost: i32 = actions.iter().map(|action| action.cost).sum();
-            let action_names: Vec<_> = actions.iter().map(|action| &action.name).collect();
-            // println!("Solution found with actions: {:?}", action_names);
-            println!("Total cost of actions: {}", total_cost);
-        }
-        Err(msg) => {
-            println!("Search failed: {}", msg);
-        }
+#[derive(Parser)]
+#[command(about = "Solve, validate and benchmark planning problems")]
+struct Cli {
+    // Without a subcommand, the bundled farm input is solved with GBFS
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Search for a plan and print it
+    Solve(SolveArgs),
+    /// Check that a plan file is applicable and reaches the goal
+    Validate(ValidateArgs),
+    /// Generate a random taxi instance
+    Generate(GenerateArgs),
+    /// Describe the initial state of a problem and the available algorithms and heuristics
+    Inspect(ProblemArgs),
+    /// Compare several search configurations on the same problem
+    Bench(BenchArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text, // One action per line with its parameters, as read by `validate`
+    Json, // Steps with costs and the search statistics, also read by `validate`
+    Ipc,  // `(name value...)` per action
+    Csv,
+}
+
+#[derive(Args)]
+struct ProblemArgs {
+    #[arg(long, default_value = "farm", help = "Problem name, see `inspect` for the available ones")]
+    problem: String,
+
+    #[arg(long, help = "Problem file, defaults to the bundled input of the problem")]
+    input: Option<String>,
+}
+
+#[derive(Args)]
+struct SearchArgs {
+    #[arg(long, default_value = "gbfs", help = "Search configuration, e.g. \"astar(h=taxi, w=1.5)\"")]
+    algorithm: String,
+
+    #[arg(long, help = "Heuristic expression replacing the configuration's, e.g. \"max(taxi, goalcount)\"")]
+    heuristic: Option<String>,
+
+    #[arg(long, help = "Maximum number of node expansions")]
+    max_expansions: Option<usize>,
+
+    #[arg(long, value_parser = parse_seconds, help = "Time limit in seconds")]
+    time_limit: Option<Duration>,
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    #[command(flatten)]
+    problem: ProblemArgs,
+
+    #[command(flatten)]
+    search: SearchArgs,
+
+    #[arg(long, value_enum, default_value = "text")]
+    output: OutputFormat,
+
+    #[arg(long, help = "Include the state after each step in JSON output")]
+    states: bool,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    #[command(flatten)]
+    problem: ProblemArgs,
+
+    #[arg(long, help = "Plan file, as printed by `solve` in text or JSON format")]
+    plan: String,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..), help = "Width of the map")]
+    width: i32,
+
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..), help = "Height of the map")]
+    height: i32,
+
+    #[arg(long, help = "Number of passengers")]
+    passengers: usize,
+
+    #[arg(long, help = "Number of impassable tiles")]
+    impassable: usize,
+
+    #[arg(long, help = "File to write, standard output if omitted")]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    #[command(flatten)]
+    problem: ProblemArgs,
+
+    #[arg(long = "algorithm", required = true, help = "Search configuration to run, repeatable")]
+    algorithms: Vec<String>,
+
+    #[arg(long, help = "Heuristic expression replacing every configuration's")]
+    heuristic: Option<String>,
+
+    #[arg(long, help = "Maximum number of node expansions per run")]
+    max_expansions: Option<usize>,
+
+    #[arg(long, value_parser = parse_seconds, help = "Time limit in seconds per run")]
+    time_limit: Option<Duration>,
+
+    #[arg(long, default_value_t = 1, help = "Runs per configuration, the fastest is reported")]
+    repeat: usize,
+}
+
+// Non-negative, finite number of seconds
+fn parse_seconds(text: &str) -> Result<Duration, String> {
+    let seconds: f64 = text.parse().map_err(|_| format!("'{}' is not a number of seconds", text))?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("'{}' is not a non-negative, finite number of seconds", text))
+}
+
+type CommandResult = Result<ExitCode, Box<dyn Error>>;
+
+// A command that runs on a problem chosen by name
+trait ProblemCommand {
+    fn run(&self, problem: &BoxedProblem, registry: &Registry<BoxedProblem>) -> CommandResult;
+}
+
+fn with_problem<C: ProblemCommand>(args: &ProblemArgs, command: &C) -> CommandResult {
+    let problem = ProblemRegistry::new().load(&args.problem, args.input.as_deref())?;
+    let registry = problem.registry();
+    command.run(&problem, &registry)
+}
+
+fn build_solver<'a, P: Problem + 'a>(
+    problem: &'a P,
+    registry: &Registry<'a, P>,
+    algorithm: &str,
+    heuristic: Option<&str>,
+    max_expansions: Option<usize>,
+    time_limit: Option<Duration>,
+) -> Result<Solver<'a, P>, Box<dyn Error>> {
+    let mut configuration = registry.build(algorithm)?;
+    if let Some(heuristic) = heuristic {
+        configuration.heuristic = registry.resolve_heuristic(&parse_config(heuristic)?)?;
+    }
+    let mut solver = Solver::new(problem).configure(configuration);
+    if let Some(max_expansions) = max_expansions {
+        solver = solver.max_expansions(max_expansions);
     }
+    if let Some(time_limit) = time_limit {
+        solver = solver.time_limit(time_limit);
+    }
+    Ok(solver)
+}
 
+fn summary(result: &SolveResult<Action>) -> String {
+    let statistics = &result.statistics;
+    format!(
+        "{} expanded, {} created, {} unique, {} pruned in {:?}",
+        statistics.nodes_expanded, statistics.nodes_created, statistics.unique_nodes_created, statistics.nodes_pruned, result.elapsed
+    )
 }
 
-fn solve_farm_problem(){
-    let farm_problem = FarmProblem::new_from_json("inputs/farm_problem/input.json");
-    let initial_state = farm_problem.create_initial_state();
-    let initial_node = farm_problem.create_initial_node(initial_state);
-    let mut tree = SearchTree::new(initial_node.state.clone());
-    let gbfs_queue = GBFSQueue::new();
-    let astar_queue = AStarQueue::new();
-
-    match generic_search(
-        &mut tree, // Pass mutable reference to tree
-        |state| farm_problem.get_possible_actions(state),
-        |state, action| farm_problem.apply_action(state, action),
-        |state| farm_problem.is_goal_state(state),
-        gbfs_queue,
-        |state| farm_problem.heuristic(state),
-    ) {
-        Ok(actions) => {
-            let total_cost: i32 = actions.iter().map(|action| action.cost).sum();
-            let action_names: Vec<_> = actions.iter().map(|action| &action.name).collect();
-            // println!("Solution found with actions: {:?}", action_names);
-            println!("Total cost of actions: {}", total_cost);
-        }
-        Err(msg) => {
-            println!("Search failed: {}", msg);
+impl ProblemCommand for SolveArgs {
+    fn run(&self, problem: &BoxedProblem, registry: &Registry<BoxedProblem>) -> CommandResult {
+        let search = &self.search;
+        let solver = build_solver(problem, registry, &search.algorithm, search.heuristic.as_deref(), search.max_expansions, search.time_limit)?;
+        let result = solver.solve();
+
+        match self.output {
+            // The statistics and the plan in the format of `read_plan_json`
+            OutputFormat::Json => {
+                let statistics = &result.statistics;
+                let actions = result.plan.as_deref().unwrap_or_default();
+                let states = self.states.then(|| plan_states(problem, &problem.initial_state(), actions));
+                let mut output = plan_to_json(actions, states.as_deref())?;
+                output["algorithm"] = json!(search.algorithm);
+                output["heuristic"] = json!(search.heuristic);
+                output["solved"] = json!(result.is_solved());
+                output["error"] = json!(result.plan.as_ref().err());
+                output["statistics"] = json!({
+                    "nodes_expanded": statistics.nodes_expanded,
+                    "nodes_created": statistics.nodes_created,
+                    "unique_nodes_created": statistics.unique_nodes_created,
+                    "nodes_pruned": statistics.nodes_pruned,
+                });
+                output["elapsed_seconds"] = json!(result.elapsed.as_secs_f64());
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            // The plan goes to standard output so that it can be saved and validated
+            format => {
+                match &result.plan {
+                    Ok(actions) => {
+                        match format {
+                            OutputFormat::Ipc => print!("{}", write_plan_ipc(actions)),
+                            OutputFormat::Csv => print!("{}", write_plan_csv(actions)?),
+                            _ => print!("{}", write_plan(actions)),
+                        }
+                        eprintln!("Total cost of actions: {}", result.cost()?);
+                    }
+                    Err(message) => eprintln!("Search failed: {}", message),
+                }
+                eprintln!("{}", summary(&result));
+            }
         }
+        Ok(if result.is_solved() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
     }
+}
+
+impl ProblemCommand for ValidateArgs {
+    fn run(&self, problem: &BoxedProblem, _registry: &Registry<BoxedProblem>) -> CommandResult {
+        let text = std::fs::read_to_string(&self.plan)?;
+        let plan = if text.trim_start().starts_with('{') { read_plan_json(&text)? } else { read_plan(&text)? };
+        let initial_state = problem.initial_state();
+        let plan = resolve_plan(problem, &initial_state, &plan);
+        let validation = validate_plan(problem, &initial_state, &plan)?;
 
+        if let Some(step) = validation.first_inapplicable_step {
+            println!("Step {} is not applicable: {}", step + 1, format_action(&plan[step]));
+        } else if !validation.reaches_goal {
+            println!("All {} actions apply but the plan does not reach the goal", plan.len());
+        } else {
+            println!("Plan is valid: {} actions, total cost {:?}", plan.len(), validation.cost);
+        }
+        Ok(if validation.is_valid() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+    }
 }
 
+impl ProblemCommand for ProblemArgs {
+    fn run(&self, problem: &BoxedProblem, registry: &Registry<BoxedProblem>) -> CommandResult {
+        let state = problem.initial_state();
+        println!("Initial state: {:?}", state);
+        println!("Goal state: {}", problem.is_goal_state(&state));
+        println!("Dead end: {}", problem.is_dead_end(&state));
+        println!("Heuristic: {}", problem.heuristic(&state));
+        if let Some(goal) = problem.goal() {
+            println!("Goal conditions: {:?}", goal.conditions);
+        }
+        if let Some(satisfaction) = problem.goal_satisfaction(&state) {
+            println!("Goal conditions satisfied: {} of {}", satisfaction.satisfied, satisfaction.total);
+        }
+        let actions = problem.get_possible_actions(&state);
+        println!("Applicable actions ({}):", actions.len());
+        for action in &actions {
+            println!("    {}", format_action(action));
+        }
+        println!("Problems: {}", ProblemRegistry::new().names().join(", "));
+        println!("Algorithms: {}", registry.algorithm_names().join(", "));
+        println!("Heuristics: {}", registry.heuristic_names().join(", "));
+        Ok(ExitCode::SUCCESS)
+    }
+}
 
-fn main() {
-    let start_time = Instant::now();
-    solve_farm_problem();
-    let elapsed_time = start_time.elapsed(); // Calculate elapsed time
-    println!("Execution time: {:?}", elapsed_time);
+impl ProblemCommand for BenchArgs {
+    fn run(&self, problem: &BoxedProblem, registry: &Registry<BoxedProblem>) -> CommandResult {
+        println!("{:<40} {:>8} {:>10} {:>10} {:>12}", "configuration", "cost", "expanded", "created", "time");
+        for algorithm in &self.algorithms {
+            let solver = build_solver(problem, registry, algorithm, self.heuristic.as_deref(), self.max_expansions, self.time_limit)?;
+            let result = (0..self.repeat.max(1)).map(|_| solver.solve()).min_by_key(|result| result.elapsed).unwrap();
+            let cost = result.cost().map_or_else(|_| "-".to_string(), |cost| cost.to_string());
+            println!(
+                "{:<40} {:>8} {:>10} {:>10} {:>12}",
+                algorithm,
+                cost,
+                result.statistics.nodes_expanded,
+                result.statistics.nodes_created,
+                format!("{:.3?}", result.elapsed)
+            );
+        }
+        Ok(ExitCode::SUCCESS)
+    }
 }
 
+fn generate(args: &GenerateArgs) -> CommandResult {
+    let json_data = generate_input(args.width, args.height, args.passengers, args.impassable)?;
+    match &args.output {
+        Some(path) => std::fs::write(path, json_data)?,
+        None => println!("{}", json_data),
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
+fn main() -> ExitCode {
+    let command = match Cli::parse().command {
+        Some(command) => command,
+        None => Cli::parse_from(["black-box-planning-solver", "solve"]).command.unwrap(),
+    };
+    let result = match &command {
+        Command::Solve(args) => with_problem(&args.problem, args),
+        Command::Validate(args) => with_problem(&args.problem, args),
+        Command::Generate(args) => generate(args),
+        Command::Inspect(args) => with_problem(args, args),
+        Command::Bench(args) => with_problem(&args.problem, args),
+    };
+    result.unwrap_or_else(|error| {
+        eprintln!("Error: {}", error);
+        ExitCode::FAILURE
+    })
+}
@@ -1,4 +1,4 @@
-use crate::search::{state::{State, Value}, action::Action};
+use crate::search::{state::State, action::Action};
 
 pub struct MCTSNode {
     pub state: State,                 // Current state of the node (from Node)
@@ -1 +1,4 @@
-mod mctsnode;
\ No newline at end of file
+pub mod mctsnode;
+pub mod rollout;
+pub mod nmcs;
+pub mod nrpa;
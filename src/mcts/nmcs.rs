@@ -0,0 +1,90 @@
+use rand::Rng;
+use crate::problems::problem::Problem;
+use crate::mcts::rollout::{random_playout, Rollout};
+
+// Nested Monte Carlo Search: at every step each action is evaluated by a search of level - 1
+// (a random playout at level 0), and the move leading to the best sequence found so far is played
+pub fn nested_monte_carlo_search<P, R>(
+    problem: &P,
+    initial_state: &P::State,
+    level: usize,
+    max_depth: usize,
+    rng: &mut R,
+) -> Result<(Vec<P::Action>, P::Cost), &'static str>
+where
+    P: Problem,
+    R: Rng,
+{
+    nested_search(problem, initial_state, level, max_depth, rng)?.into_solution()
+}
+
+fn nested_search<P: Problem, R: Rng>(
+    problem: &P,
+    state: &P::State,
+    level: usize,
+    max_depth: usize,
+    rng: &mut R,
+) -> Result<Rollout<P::Action>, &'static str> {
+    if level == 0 {
+        return random_playout(problem, state, max_depth, rng);
+    }
+
+    let mut current_state = state.clone();
+    let mut played: Vec<P::Action> = Vec::new();
+    let mut best = Rollout::empty(problem, state);
+
+    while played.len() < max_depth && !problem.is_goal_state(&current_state) {
+        let successors: Vec<_> = problem.successors(&current_state).collect();
+        if successors.is_empty() {
+            break;
+        }
+
+        for (action, next_state) in successors {
+            let mut prefix = played.clone();
+            prefix.push(action);
+            let remaining_depth = max_depth - prefix.len();
+            let candidate = nested_search(problem, &next_state, level - 1, remaining_depth, rng)?.with_prefix(&prefix)?;
+            if best.actions.len() <= played.len() || candidate.is_better_than(&best) {
+                best = candidate;
+            }
+        }
+
+        // Follow the best sequence found so far by one step
+        let next_action = best.actions[played.len()].clone();
+        current_state = problem.apply_action(&current_state, &next_action);
+        played.push(next_action);
+    }
+
+    Ok(best)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::taxi_problem::taxi_problem::{small_taxi_problem, TaxiProblem, TaxiState};
+    use crate::search::state::Position;
+    use crate::search::validate_plan::validate_plan;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn create_taxi_problem() -> (TaxiProblem, TaxiState) {
+        let problem = small_taxi_problem(3, 3, &[], Position::new(0, 0), &[(Position::new(0, 2), Position::new(2, 2))]);
+        let state = problem.initial_state();
+        (problem, state)
+    }
+
+    #[test]
+    fn test_nmcs_finds_taxi_solution() {
+        let (problem, state) = create_taxi_problem();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let (actions, cost) = nested_monte_carlo_search(&problem, &state, 3, 12, &mut rng).unwrap();
+
+        let validation = validate_plan(&problem, &state, &actions).unwrap();
+        assert!(validation.is_valid());
+        assert_eq!(validation.cost, cost);
+        // Optimal plan: 2 moves, pick up, 2 moves, disembark
+        assert_eq!(cost, 6);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use rand::Rng;
+use crate::search::action::SearchAction;
+use crate::problems::problem::Problem;
+use crate::mcts::rollout::{total_cost, Rollout};
+
+// Policy weights learned by NRPA, keyed by action code (see `SearchAction::code`)
+pub type Policy = HashMap<String, f64>;
+
+// Nested Rollout Policy Adaptation: each level runs `iterations` searches of level - 1 and adapts
+// the policy towards the best sequence found, level 0 being a playout guided by the policy
+pub fn nested_rollout_policy_adaptation<P, R>(
+    problem: &P,
+    initial_state: &P::State,
+    level: usize,
+    iterations: usize,
+    alpha: f64,
+    max_depth: usize,
+    rng: &mut R,
+) -> Result<(Vec<P::Action>, P::Cost), &'static str>
+where
+    P: Problem,
+    R: Rng,
+{
+    let search = Nrpa { problem, initial_state, iterations, alpha, max_depth };
+    search.run(level, Policy::new(), rng)?.into_solution()
+}
+
+struct Nrpa<'a, P: Problem> {
+    problem: &'a P,
+    initial_state: &'a P::State,
+    iterations: usize,
+    alpha: f64,
+    max_depth: usize,
+}
+
+impl<P: Problem> Nrpa<'_, P> {
+    fn run<R: Rng>(&self, level: usize, mut policy: Policy, rng: &mut R) -> Result<Rollout<P::Action>, &'static str> {
+        if level == 0 {
+            return self.playout(&policy, rng);
+        }
+
+        let mut best: Option<Rollout<P::Action>> = None;
+        for _ in 0..self.iterations {
+            let candidate = self.run(level - 1, policy.clone(), rng)?;
+            // Ties are accepted so that the search keeps moving across plateaus
+            if best.as_ref().is_none_or(|best| !best.is_better_than(&candidate)) {
+                best = Some(candidate);
+            }
+            if let Some(best) = &best {
+                policy = self.adapt(&policy, &best.actions);
+            }
+        }
+        Ok(best.unwrap_or_else(|| Rollout::empty(self.problem, self.initial_state)))
+    }
+
+    // Sample actions with probability proportional to exp(policy weight)
+    fn playout<R: Rng>(&self, policy: &Policy, rng: &mut R) -> Result<Rollout<P::Action>, &'static str> {
+        let mut current_state = self.initial_state.clone();
+        let mut actions = Vec::new();
+        let mut reached_goal = self.problem.is_goal_state(&current_state);
+
+        while actions.len() < self.max_depth && !reached_goal {
+            let mut possible_actions = self.problem.get_possible_actions(&current_state);
+            if possible_actions.is_empty() {
+                break;
+            }
+            let weights: Vec<f64> = possible_actions.iter().map(|action| Self::weight(policy, action)).collect();
+            let mut sample = rng.gen::<f64>() * weights.iter().sum::<f64>();
+            let mut chosen = weights.len() - 1;
+            for (index, weight) in weights.iter().enumerate() {
+                if sample < *weight {
+                    chosen = index;
+                    break;
+                }
+                sample -= weight;
+            }
+            let action = possible_actions.swap_remove(chosen);
+            current_state = self.problem.apply_action(&current_state, &action);
+            actions.push(action);
+            reached_goal = self.problem.is_goal_state(&current_state);
+        }
+
+        Ok(Rollout {
+            cost: total_cost(&actions)?,
+            actions,
+            reached_goal,
+            heuristic: self.problem.heuristic(&current_state),
+        })
+    }
+
+    // Move the policy towards the given sequence by a gradient step of size alpha
+    fn adapt(&self, policy: &Policy, sequence: &[P::Action]) -> Policy {
+        let mut adapted = policy.clone();
+        let mut current_state = self.initial_state.clone();
+
+        for played in sequence {
+            let possible_actions = self.problem.get_possible_actions(&current_state);
+            let total: f64 = possible_actions.iter().map(|action| Self::weight(policy, action)).sum();
+
+            *adapted.entry(played.code()).or_insert(0.0) += self.alpha;
+            for action in &possible_actions {
+                let probability = Self::weight(policy, action) / total;
+                *adapted.entry(action.code()).or_insert(0.0) -= self.alpha * probability;
+            }
+            current_state = self.problem.apply_action(&current_state, played);
+        }
+        adapted
+    }
+
+    fn weight(policy: &Policy, action: &P::Action) -> f64 {
+        policy.get(&action.code()).copied().unwrap_or(0.0).exp()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::action::Action;
+    use crate::search::state::{State, Value};
+    use crate::search::validate_plan::validate_plan;
+
+    // Reach a counter of exactly 5; "add" costs 1 per unit, "add_five" costs 2
+    struct CounterProblem;
+
+    impl Problem for CounterProblem {
+        type State = State;
+        type Action = Action;
+        type Cost = i32;
+
+        fn initial_state(&self) -> State {
+            let mut state = State::new();
+            state.insert_field("counter".to_string(), Value::Int(0));
+            state
+        }
+
+        fn get_possible_actions(&self, state: &State) -> Vec<Action> {
+            match state.get_field("counter") {
+                Some(Value::Int(counter)) if *counter < 5 => {
+                    let mut actions = vec![Action::new("add".to_string(), 1, HashMap::new())];
+                    if *counter == 0 {
+                        actions.push(Action::new("add_five".to_string(), 2, HashMap::new()));
+                    }
+                    actions
+                }
+                _ => Vec::new(),
+            }
+        }
+
+        fn apply_action(&self, state: &State, action: &Action) -> State {
+            let mut new_state = state.clone();
+            if let Some(Value::Int(counter)) = state.get_field("counter") {
+                let increment = if action.name == "add_five" { 5 } else { 1 };
+                new_state.insert_field("counter".to_string(), Value::Int(counter + increment));
+            }
+            new_state
+        }
+
+        fn is_goal_state(&self, state: &State) -> bool {
+            state.get_field("counter") == Some(&Value::Int(5))
+        }
+
+        fn heuristic(&self, state: &State) -> f64 {
+            match state.get_field("counter") {
+                Some(Value::Int(counter)) => (5 - counter) as f64,
+                _ => 0.0,
+            }
+        }
+    }
+
+    #[test]
+    fn test_nrpa_finds_cheapest_sequence() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let state = CounterProblem.initial_state();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let (actions, cost) = nested_rollout_policy_adaptation(&CounterProblem, &state, 2, 10, 1.0, 10, &mut rng).unwrap();
+
+        let validation = validate_plan(&CounterProblem, &state, &actions).unwrap();
+        assert!(validation.is_valid());
+        assert_eq!(validation.cost, cost);
+        assert_eq!(cost, 2);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].name, "add_five");
+    }
+}
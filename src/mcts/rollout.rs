@@ -0,0 +1,109 @@
+use rand::Rng;
+use crate::search::action::{Action, SearchAction};
+use crate::search::cost::Cost;
+use crate::problems::problem::Problem;
+
+// A complete action sequence produced by a playout, together with how far it got
+#[derive(Debug, Clone)]
+pub struct Rollout<A: SearchAction = Action> {
+    pub actions: Vec<A>,
+    pub cost: A::Cost,
+    pub reached_goal: bool,
+    pub heuristic: f64, // Heuristic value of the last state, used to rank sequences that missed the goal
+}
+
+impl<A: SearchAction> Rollout<A> {
+    pub fn empty<P: Problem<Action = A>>(problem: &P, state: &P::State) -> Self {
+        Rollout {
+            actions: Vec::new(),
+            cost: A::Cost::zero(),
+            reached_goal: problem.is_goal_state(state),
+            heuristic: problem.heuristic(state),
+        }
+    }
+
+    // Sequences reaching the goal beat those that do not; among those, lower cost wins.
+    // Sequences that miss the goal are ranked by the heuristic value of their last state.
+    pub fn is_better_than(&self, other: &Rollout<A>) -> bool {
+        match (self.reached_goal, other.reached_goal) {
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => self.cost < other.cost,
+            (false, false) => {
+                self.heuristic < other.heuristic
+                    || (self.heuristic == other.heuristic && self.cost < other.cost)
+            }
+        }
+    }
+
+    // Prepend a prefix of already executed actions to this rollout
+    pub fn with_prefix(mut self, prefix: &[A]) -> Result<Self, &'static str> {
+        let mut actions = prefix.to_vec();
+        actions.append(&mut self.actions);
+        self.cost = total_cost(&actions)?;
+        self.actions = actions;
+        Ok(self)
+    }
+
+    pub fn into_solution(self) -> Result<(Vec<A>, A::Cost), &'static str> {
+        if self.reached_goal {
+            Ok((self.actions, self.cost))
+        } else {
+            Err("No solution found")
+        }
+    }
+}
+
+// Sum of the action costs, or an error if it overflows the cost type
+pub fn total_cost<A: SearchAction>(actions: &[A]) -> Result<A::Cost, &'static str> {
+    actions.iter().try_fold(A::Cost::zero(), |total, action| {
+        total.checked_add(action.cost()).ok_or("Path cost overflow")
+    })
+}
+
+// Play uniformly random actions from `state` until the goal, a dead end or `max_depth` steps
+pub fn random_playout<P: Problem, R: Rng>(
+    problem: &P,
+    state: &P::State,
+    max_depth: usize,
+    rng: &mut R,
+) -> Result<Rollout<P::Action>, &'static str> {
+    let mut current_state = state.clone();
+    let mut actions = Vec::new();
+    let mut reached_goal = problem.is_goal_state(&current_state);
+
+    while actions.len() < max_depth && !reached_goal {
+        let mut possible_actions = problem.get_possible_actions(&current_state);
+        if possible_actions.is_empty() {
+            break;
+        }
+        let action = possible_actions.swap_remove(rng.gen_range(0..possible_actions.len()));
+        current_state = problem.apply_action(&current_state, &action);
+        actions.push(action);
+        reached_goal = problem.is_goal_state(&current_state);
+    }
+
+    Ok(Rollout {
+        cost: total_cost(&actions)?,
+        actions,
+        reached_goal,
+        heuristic: problem.heuristic(&current_state),
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_cost_overflow_is_an_error() {
+        let steps = vec![Action::new("step".to_string(), i32::MAX / 2 + 1, HashMap::new()); 2];
+        assert_eq!(total_cost(&steps[..1]), Ok(i32::MAX / 2 + 1));
+        assert_eq!(total_cost(&steps), Err("Path cost overflow"));
+
+        let rollout = Rollout { actions: steps[..1].to_vec(), cost: steps[0].cost, reached_goal: true, heuristic: 0.0 };
+        assert_eq!(rollout.with_prefix(&steps[1..]).unwrap_err(), "Path cost overflow");
+    }
+}
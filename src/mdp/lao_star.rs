@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+use crate::problems::stochastic_problem::StochasticProblem;
+use crate::mdp::policy::Policy;
+use crate::mdp::value_function::ValueFunction;
+
+// LAO* in its improved (ILAO*) form: each iteration walks the best partial solution graph depth-first,
+// expands its unexpanded tip states and backs up every visited state in post-order.
+// Terminates once the solution graph has no tips left and all its residuals are below epsilon.
+pub fn lao_star<P: StochasticProblem>(
+    problem: &P,
+    initial_state: &P::State,
+    epsilon: f64,
+    max_iterations: usize,
+) -> Result<Policy<P::State, P::Action>, &'static str> {
+    let mut values = ValueFunction::new(problem);
+    let mut expanded: HashSet<P::State> = HashSet::new();
+
+    for _ in 0..max_iterations {
+        let mut visited = HashSet::new();
+        let mut postorder = Vec::new();
+        let mut expanded_tip = false;
+        let mut stack = vec![(initial_state.clone(), false)];
+
+        while let Some((state, children_done)) = stack.pop() {
+            if children_done {
+                postorder.push(state);
+                continue;
+            }
+            if !visited.insert(state.clone()) || problem.is_goal_state(&state) {
+                continue;
+            }
+            if expanded.insert(state.clone()) {
+                // Tip of the solution graph: expand it but do not descend further in this iteration
+                expanded_tip = true;
+                postorder.push(state);
+                continue;
+            }
+            stack.push((state.clone(), true));
+            if let Some((action, _)) = values.greedy_action(&state) {
+                for (successor, _) in problem.get_action_outcomes(&state, &action) {
+                    stack.push((successor, false));
+                }
+            }
+        }
+
+        let mut max_residual: f64 = 0.0;
+        for state in &postorder {
+            max_residual = max_residual.max(values.update(state));
+        }
+
+        if !expanded_tip && max_residual < epsilon {
+            let policy = values.extract_policy(initial_state);
+            if policy.expected_cost.is_infinite() {
+                return Err("No solution found");
+            }
+            return Ok(policy);
+        }
+    }
+    Err("LAO* did not converge")
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use rand::Rng;
+use crate::problems::stochastic_problem::StochasticProblem;
+use crate::mdp::policy::Policy;
+use crate::mdp::value_function::ValueFunction;
+
+// Labeled RTDP: greedy trials with sampled outcomes, after which states whose greedy
+// envelope has converged are labeled solved. Stops when the initial state is solved.
+pub fn lrtdp<P, R>(
+    problem: &P,
+    initial_state: &P::State,
+    epsilon: f64,
+    max_trials: usize,
+    rng: &mut R,
+) -> Result<Policy<P::State, P::Action>, &'static str>
+where
+    P: StochasticProblem,
+    R: Rng,
+{
+    let mut search = Lrtdp {
+        problem,
+        values: ValueFunction::new(problem),
+        solved: HashSet::new(),
+        epsilon,
+    };
+
+    for _ in 0..max_trials {
+        if search.is_solved(initial_state) {
+            let policy = search.values.extract_policy(initial_state);
+            if policy.expected_cost.is_infinite() {
+                return Err("No solution found");
+            }
+            return Ok(policy);
+        }
+        search.trial(initial_state, rng);
+    }
+    Err("LRTDP did not converge")
+}
+
+struct Lrtdp<'a, P: StochasticProblem> {
+    problem: &'a P,
+    values: ValueFunction<'a, P>,
+    solved: HashSet<P::State>,
+    epsilon: f64,
+}
+
+impl<P: StochasticProblem> Lrtdp<'_, P> {
+    fn is_solved(&self, state: &P::State) -> bool {
+        self.solved.contains(state) || self.problem.is_goal_state(state)
+    }
+
+    fn trial<R: Rng>(&mut self, initial_state: &P::State, rng: &mut R) {
+        let mut visited = Vec::new();
+        let mut state = initial_state.clone();
+
+        while !self.is_solved(&state) {
+            visited.push(state.clone());
+            self.values.update(&state);
+            let action = match self.values.greedy_action(&state) {
+                Some((action, _)) => action,
+                None => break, // Dead end, given an infinite value by the update above
+            };
+            state = self.problem.sample_action(&state, &action, rng);
+        }
+
+        while let Some(state) = visited.pop() {
+            if !self.check_solved(&state) {
+                break;
+            }
+        }
+    }
+
+    // Label `state` and its greedy envelope solved if all their residuals are below epsilon,
+    // otherwise back them up
+    fn check_solved(&mut self, state: &P::State) -> bool {
+        let mut converged = true;
+        let mut open = Vec::new();
+        let mut closed = Vec::new();
+        let mut seen = HashSet::new();
+
+        if !self.is_solved(state) {
+            seen.insert(state.clone());
+            open.push(state.clone());
+        }
+
+        while let Some(current) = open.pop() {
+            let greedy = self.values.greedy_action(&current);
+            closed.push(current.clone());
+            let action = match greedy {
+                Some((action, q)) => {
+                    if (q - self.values.value(&current)).abs() > self.epsilon {
+                        converged = false;
+                        continue;
+                    }
+                    action
+                }
+                None => continue,
+            };
+            for (successor, _) in self.problem.get_action_outcomes(&current, &action) {
+                if !self.is_solved(&successor) && seen.insert(successor.clone()) {
+                    open.push(successor);
+                }
+            }
+        }
+
+        if converged {
+            self.solved.extend(closed);
+        } else {
+            while let Some(current) = closed.pop() {
+                self.values.update(&current);
+            }
+        }
+        converged
+    }
+}
@@ -0,0 +1,50 @@
+pub mod policy;
+pub mod value_function;
+pub mod value_iteration;
+pub mod lao_star;
+pub mod lrtdp;
+
+
+#[cfg(test)]
+mod tests {
+    use crate::mdp::{lao_star::lao_star, lrtdp::lrtdp, value_iteration::value_iteration};
+    use crate::problems::problem::Problem;
+    use crate::problems::taxi_problem::stochastic_taxi_problem::StochasticTaxiProblem;
+    use crate::problems::taxi_problem::taxi_problem::{small_taxi_problem, TaxiProblem, TaxiState};
+    use crate::search::state::Position;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Corridor of three tiles: pick up at the left end, drop off at the right end.
+    // Each move succeeds with probability 0.5, so it costs 2 in expectation: 1 + 2 + 2 + 1 = 6.
+    fn create_problem() -> (StochasticTaxiProblem, TaxiState) {
+        let taxi = small_taxi_problem(3, 1, &[], Position::new(0, 0), &[(Position::new(0, 0), Position::new(2, 0))]);
+        let state = taxi.initial_state();
+        (StochasticTaxiProblem::new(taxi, 0.5).unwrap(), state)
+    }
+
+    #[test]
+    fn test_value_iteration() {
+        let (problem, state) = create_problem();
+        let policy = value_iteration(&problem, &state, 1e-6, 1000).unwrap();
+        assert!((policy.expected_cost - 6.0).abs() < 1e-3);
+        assert_eq!(policy.get_action(&state), Some(&TaxiProblem::pick_up("passenger1")));
+    }
+
+    #[test]
+    fn test_lao_star() {
+        let (problem, state) = create_problem();
+        let policy = lao_star(&problem, &state, 1e-6, 1000).unwrap();
+        assert!((policy.expected_cost - 6.0).abs() < 1e-3);
+        assert_eq!(policy.get_action(&state), Some(&TaxiProblem::pick_up("passenger1")));
+    }
+
+    #[test]
+    fn test_lrtdp() {
+        let (problem, state) = create_problem();
+        let mut rng = StdRng::seed_from_u64(5);
+        let policy = lrtdp(&problem, &state, 1e-6, 10000, &mut rng).unwrap();
+        assert!((policy.expected_cost - 6.0).abs() < 1e-3);
+        assert_eq!(policy.get_action(&state), Some(&TaxiProblem::pick_up("passenger1")));
+    }
+}
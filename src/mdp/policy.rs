@@ -0,0 +1,16 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::search::{state::State, action::Action};
+
+// Result of an MDP solver: the action to take in every state reachable under the policy
+#[derive(Debug, Clone)]
+pub struct Policy<S = State, A = Action> {
+    pub actions: HashMap<S, A>,
+    pub expected_cost: f64, // Expected cost to reach the goal from the initial state
+}
+
+impl<S: Eq + Hash, A> Policy<S, A> {
+    pub fn get_action(&self, state: &S) -> Option<&A> {
+        self.actions.get(state)
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+use crate::search::action::SearchAction;
+use crate::search::cost::Cost;
+use crate::problems::stochastic_problem::StochasticProblem;
+use crate::mdp::policy::Policy;
+
+// Table of state values shared by the MDP solvers.
+// States not in the table are valued by `Problem::heuristic`, goal states cost 0 and
+// states without applicable actions are dead ends with infinite cost.
+pub struct ValueFunction<'a, P: StochasticProblem> {
+    problem: &'a P,
+    pub values: HashMap<P::State, f64>,
+}
+
+impl<'a, P: StochasticProblem> ValueFunction<'a, P> {
+    pub fn new(problem: &'a P) -> Self {
+        ValueFunction {
+            problem,
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn value(&self, state: &P::State) -> f64 {
+        if let Some(value) = self.values.get(state) {
+            return *value;
+        }
+        if self.problem.is_goal_state(state) {
+            0.0
+        } else {
+            self.problem.heuristic(state)
+        }
+    }
+
+    // Expected cost of applying `action` in `state` and following the current values afterwards
+    pub fn q_value(&self, state: &P::State, action: &P::Action) -> f64 {
+        let future_cost: f64 = self
+            .problem
+            .get_action_outcomes(state, action)
+            .iter()
+            .map(|(successor, probability)| probability * self.value(successor))
+            .sum();
+        action.cost().to_f64() + future_cost
+    }
+
+    // Action minimising the q-value, with that q-value; None for goal states and dead ends
+    pub fn greedy_action(&self, state: &P::State) -> Option<(P::Action, f64)> {
+        if self.problem.is_goal_state(state) {
+            return None;
+        }
+        let mut best: Option<(P::Action, f64)> = None;
+        for action in self.problem.get_possible_actions(state) {
+            let q = self.q_value(state, &action);
+            if best.as_ref().is_none_or(|(_, best_q)| q < *best_q) {
+                best = Some((action, q));
+            }
+        }
+        best
+    }
+
+    // Bellman backup of `state`; returns the residual (absolute change of its value)
+    pub fn update(&mut self, state: &P::State) -> f64 {
+        let old_value = self.value(state);
+        let new_value = match self.greedy_action(state) {
+            Some((_, q)) => q,
+            None if self.problem.is_goal_state(state) => 0.0,
+            None => f64::INFINITY,
+        };
+        self.values.insert(state.clone(), new_value);
+        residual(old_value, new_value)
+    }
+
+    // Greedy policy over all states reachable from `initial_state` when following it
+    pub fn extract_policy(&self, initial_state: &P::State) -> Policy<P::State, P::Action> {
+        let mut actions = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![initial_state.clone()];
+
+        while let Some(state) = stack.pop() {
+            if !visited.insert(state.clone()) {
+                continue;
+            }
+            if let Some((action, _)) = self.greedy_action(&state) {
+                for (successor, _) in self.problem.get_action_outcomes(&state, &action) {
+                    stack.push(successor);
+                }
+                actions.insert(state, action);
+            }
+        }
+
+        Policy {
+            actions,
+            expected_cost: self.value(initial_state),
+        }
+    }
+}
+
+fn residual(old_value: f64, new_value: f64) -> f64 {
+    // Two infinite values are equal, but their difference is NaN
+    if old_value == new_value {
+        0.0
+    } else {
+        (new_value - old_value).abs()
+    }
+}
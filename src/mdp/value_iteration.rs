@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use crate::problems::stochastic_problem::StochasticProblem;
+use crate::mdp::policy::Policy;
+use crate::mdp::value_function::ValueFunction;
+
+// Value iteration over every state reachable from the initial state.
+// Only suitable for state spaces small enough to be enumerated up front.
+pub fn value_iteration<P: StochasticProblem>(
+    problem: &P,
+    initial_state: &P::State,
+    epsilon: f64,
+    max_iterations: usize,
+) -> Result<Policy<P::State, P::Action>, &'static str> {
+    let states = enumerate_states(problem, initial_state);
+    let mut values = ValueFunction::new(problem);
+
+    for _ in 0..max_iterations {
+        let mut max_residual: f64 = 0.0;
+        for state in &states {
+            max_residual = max_residual.max(values.update(state));
+        }
+        if max_residual < epsilon {
+            let policy = values.extract_policy(initial_state);
+            if policy.expected_cost.is_infinite() {
+                return Err("No solution found");
+            }
+            return Ok(policy);
+        }
+    }
+    Err("Value iteration did not converge")
+}
+
+fn enumerate_states<P: StochasticProblem>(problem: &P, initial_state: &P::State) -> Vec<P::State> {
+    let mut visited = HashSet::new();
+    let mut states = Vec::new();
+    let mut stack = vec![initial_state.clone()];
+
+    while let Some(state) = stack.pop() {
+        if !visited.insert(state.clone()) {
+            continue;
+        }
+        if !problem.is_goal_state(&state) {
+            for action in problem.get_possible_actions(&state) {
+                for (successor, _) in problem.get_action_outcomes(&state, &action) {
+                    stack.push(successor);
+                }
+            }
+        }
+        states.push(state);
+    }
+    states
+}
@@ -0,0 +1,295 @@
+use std::any::Any;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use serde::{Serialize, Serializer};
+use crate::config::registry::Registry;
+use crate::problems::problem::Problem;
+use crate::search::action::Action;
+use crate::search::goal::{Goal, GoalSatisfaction};
+use crate::search::schema::StateSchema;
+
+// State of a `BoxedProblem`: the state of the wrapped problem behind a shared pointer, so that
+// cloning it during search stays cheap whatever its type
+#[derive(Clone)]
+pub struct DynState(Rc<dyn AnyState>);
+
+trait AnyState {
+    fn as_any(&self) -> &dyn Any;
+    fn equals(&self, other: &dyn AnyState) -> bool;
+    fn hash_into(&self, hasher: &mut dyn Hasher);
+    fn debug(&self, f: &mut fmt::Formatter) -> fmt::Result;
+    fn to_json(&self) -> Result<serde_json::Value, serde_json::Error>;
+}
+
+impl<S: Eq + Hash + fmt::Debug + Serialize + 'static> AnyState for S {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn equals(&self, other: &dyn AnyState) -> bool {
+        other.as_any().downcast_ref::<S>() == Some(self)
+    }
+
+    fn hash_into(&self, mut hasher: &mut dyn Hasher) {
+        self.hash(&mut hasher);
+    }
+
+    fn debug(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+}
+
+impl DynState {
+    pub fn new<S: Eq + Hash + fmt::Debug + Serialize + 'static>(state: S) -> Self {
+        DynState(Rc::new(state))
+    }
+
+    pub fn downcast_ref<S: 'static>(&self) -> Option<&S> {
+        self.0.as_any().downcast_ref()
+    }
+}
+
+impl PartialEq for DynState {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equals(other.0.as_ref())
+    }
+}
+
+impl Eq for DynState {}
+
+impl Hash for DynState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_into(state);
+    }
+}
+
+// Serialized as the wrapped state
+impl Serialize for DynState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.to_json().map_err(serde::ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl fmt::Debug for DynState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.debug(f)
+    }
+}
+
+// Object-safe counterpart of `Problem` for problems over `search::action::Action`. States are
+// passed as `DynState`; handing a problem the state of another problem panics.
+pub trait DynProblem {
+    fn initial_state(&self) -> DynState;
+    fn get_possible_actions(&self, state: &DynState) -> Vec<Action>;
+    fn apply_action(&self, state: &DynState, action: &Action) -> DynState;
+    fn successors(&self, state: &DynState) -> Vec<(Action, DynState)>;
+    fn is_goal_state(&self, state: &DynState) -> bool;
+    fn heuristic(&self, state: &DynState) -> f64;
+    fn is_dead_end(&self, state: &DynState) -> bool;
+    fn is_applicable(&self, state: &DynState, action: &Action) -> bool;
+    fn goal(&self) -> Option<Goal>;
+    fn goal_satisfaction(&self, state: &DynState) -> Option<GoalSatisfaction>;
+    fn schema(&self) -> Option<StateSchema>;
+    // Add the named heuristics of the wrapped problem, such as `taxi` or `goalcount`
+    fn register_heuristics<'a>(&'a self, registry: &mut Registry<'a, BoxedProblem>);
+}
+
+// A problem with its own state type erased, so that problems can be chosen at runtime. It
+// implements `Problem` and works with every search. Its states serialize as the wrapped states.
+pub struct BoxedProblem(Box<dyn DynProblem>);
+
+// Builds the configuration registry of a typed problem, e.g. `config::registry::taxi_registry`
+pub type RegistryBuilder<P> = for<'a> fn(&'a P) -> Registry<'a, P>;
+
+struct Erased<P: Problem> {
+    problem: P,
+    registry: RegistryBuilder<P>,
+}
+
+impl<P: Problem<Action = Action, Cost = i32, State: Serialize> + 'static> Erased<P> {
+    fn state<'s>(&self, state: &'s DynState) -> &'s P::State {
+        state.downcast_ref().expect("state belongs to another problem")
+    }
+}
+
+impl<P: Problem<Action = Action, Cost = i32, State: Serialize> + 'static> DynProblem for Erased<P> {
+    fn initial_state(&self) -> DynState {
+        DynState::new(self.problem.initial_state())
+    }
+
+    fn get_possible_actions(&self, state: &DynState) -> Vec<Action> {
+        self.problem.get_possible_actions(self.state(state))
+    }
+
+    fn apply_action(&self, state: &DynState, action: &Action) -> DynState {
+        DynState::new(self.problem.apply_action(self.state(state), action))
+    }
+
+    fn successors(&self, state: &DynState) -> Vec<(Action, DynState)> {
+        self.problem.successors(self.state(state)).map(|(action, next_state)| (action, DynState::new(next_state))).collect()
+    }
+
+    fn is_goal_state(&self, state: &DynState) -> bool {
+        self.problem.is_goal_state(self.state(state))
+    }
+
+    fn heuristic(&self, state: &DynState) -> f64 {
+        self.problem.heuristic(self.state(state))
+    }
+
+    fn is_dead_end(&self, state: &DynState) -> bool {
+        self.problem.is_dead_end(self.state(state))
+    }
+
+    fn is_applicable(&self, state: &DynState, action: &Action) -> bool {
+        self.problem.is_applicable(self.state(state), action)
+    }
+
+    fn goal(&self) -> Option<Goal> {
+        self.problem.goal()
+    }
+
+    fn goal_satisfaction(&self, state: &DynState) -> Option<GoalSatisfaction> {
+        self.problem.goal_satisfaction(self.state(state))
+    }
+
+    fn schema(&self) -> Option<StateSchema> {
+        self.problem.schema()
+    }
+
+    fn register_heuristics<'a>(&'a self, registry: &mut Registry<'a, BoxedProblem>) {
+        for (name, heuristic) in (self.registry)(&self.problem).heuristics() {
+            let heuristic = heuristic.clone();
+            registry.register_heuristic(name, move |state: &DynState| heuristic(self.state(state)));
+        }
+    }
+}
+
+fn default_registry<P: Problem + 'static>(problem: &P) -> Registry<'_, P> {
+    Registry::new(problem)
+}
+
+impl BoxedProblem {
+    // Wrap a problem with only the default heuristics of `Registry::new`
+    pub fn new<P: Problem<Action = Action, Cost = i32, State: Serialize> + 'static>(problem: P) -> Self {
+        Self::with_registry(problem, default_registry)
+    }
+
+    pub fn with_registry<P: Problem<Action = Action, Cost = i32, State: Serialize> + 'static>(problem: P, registry: RegistryBuilder<P>) -> Self {
+        BoxedProblem(Box::new(Erased { problem, registry }))
+    }
+
+    // Configuration registry with the bundled algorithms and the heuristics of the wrapped problem
+    pub fn registry(&self) -> Registry<'_, BoxedProblem> {
+        let mut registry = Registry::new(self);
+        self.0.register_heuristics(&mut registry);
+        registry
+    }
+}
+
+impl Problem for BoxedProblem {
+    type State = DynState;
+    type Action = Action;
+    type Cost = i32;
+
+    fn initial_state(&self) -> DynState {
+        self.0.initial_state()
+    }
+
+    fn get_possible_actions(&self, state: &DynState) -> Vec<Action> {
+        self.0.get_possible_actions(state)
+    }
+
+    fn apply_action(&self, state: &DynState, action: &Action) -> DynState {
+        self.0.apply_action(state, action)
+    }
+
+    fn successors(&self, state: &DynState) -> impl Iterator<Item = (Action, DynState)> {
+        self.0.successors(state).into_iter()
+    }
+
+    fn is_goal_state(&self, state: &DynState) -> bool {
+        self.0.is_goal_state(state)
+    }
+
+    fn heuristic(&self, state: &DynState) -> f64 {
+        self.0.heuristic(state)
+    }
+
+    fn is_dead_end(&self, state: &DynState) -> bool {
+        self.0.is_dead_end(state)
+    }
+
+    fn is_applicable(&self, state: &DynState, action: &Action) -> bool {
+        self.0.is_applicable(state, action)
+    }
+
+    fn goal(&self) -> Option<Goal> {
+        self.0.goal()
+    }
+
+    fn goal_satisfaction(&self, state: &DynState) -> Option<GoalSatisfaction> {
+        self.0.goal_satisfaction(state)
+    }
+
+    fn schema(&self) -> Option<StateSchema> {
+        self.0.schema()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::bfs::BfsQueue;
+    use crate::search::search::search_problem;
+    use std::collections::HashMap;
+
+    // Counts up to 2, generating its successors only through `successors`
+    struct OnePassCounter;
+
+    impl Problem for OnePassCounter {
+        type State = i32;
+        type Action = Action;
+        type Cost = i32;
+
+        fn initial_state(&self) -> i32 {
+            0
+        }
+
+        fn get_possible_actions(&self, _state: &i32) -> Vec<Action> {
+            Vec::new()
+        }
+
+        fn apply_action(&self, state: &i32, _action: &Action) -> i32 {
+            *state
+        }
+
+        fn successors(&self, state: &i32) -> impl Iterator<Item = (Action, i32)> {
+            std::iter::once((Action::new("add".to_string(), 1, HashMap::new()), state + 1))
+        }
+
+        fn is_goal_state(&self, state: &i32) -> bool {
+            *state == 2
+        }
+
+        fn heuristic(&self, state: &i32) -> f64 {
+            (2 - state) as f64
+        }
+    }
+
+    #[test]
+    fn test_successors_are_forwarded() {
+        let problem = BoxedProblem::new(OnePassCounter);
+        let successors: Vec<_> = problem.successors(&problem.initial_state()).collect();
+        assert_eq!(successors, vec![(Action::new("add".to_string(), 1, HashMap::new()), DynState::new(1))]);
+
+        let plan = search_problem(&problem, &problem.initial_state(), BfsQueue::new());
+        assert_eq!(plan.unwrap().len(), 2);
+    }
+}
@@ -1,16 +1,21 @@
 use std::collections::HashMap;
-use crate::search::{node::Node, state::State, action::Action, state::Value};
+use crate::search::{state::State, action::Action, state::Value, goal::{Goal, GoalCondition, GoalSatisfaction}};
 use crate::problems::problem::Problem;
-use std::rc::Rc;
 
 pub struct SimpleProblem;
 
 impl Problem for SimpleProblem {
-    fn create_initial_node(&self, initial_state: State) -> Node {
-        Node::new_empty(initial_state)
+    type State = State;
+    type Action = Action;
+    type Cost = i32;
+
+    fn initial_state(&self) -> State {
+        let mut state = State::new();
+        state.insert_field("health".to_string(), Value::Int(0));
+        state
     }
 
-    fn get_possible_actions(&self, state: &State) -> Vec<Action> {
+    fn get_possible_actions(&self, _state: &State) -> Vec<Action> {
         let mut actions = Vec::new();
 
         // Generate actions for increasing health by any integer value from 1 to 10
@@ -24,30 +29,30 @@ impl Problem for SimpleProblem {
 
     fn apply_action(&self, state: &State, action: &Action) -> State {
         let mut new_state = state.clone();
-        if let Some(Value::Int(amount)) = action.parameters.get("amount") {
-            if let Some(Value::Int(health)) = new_state.get_field("health") {
-                new_state.insert_field("health".to_string(), Value::Int(health + amount));
-            }
+        if let Some(amount) = action.parameters.get("amount").and_then(Value::as_int) {
+            new_state.update_int("health", |health| health + amount).ok();
         }
         new_state
     }
 
 
     fn is_goal_state(&self, state: &State) -> bool {
-        // Example goal check: health reaching 200
-        if let Some(Value::Int(health)) = state.get_field("health") {
-            *health >= 100000
-        } else {
-            false
-        }
+        state.get_int("health").is_ok_and(|health| health >= 100000)
     }
 
     fn heuristic(&self, state: &State) -> f64 {
-        if let Some(Value::Int(health)) = state.get_field("health") {
-            (100000.0 - *health as f64) / 5.0
-        } else {
-            0.0
+        match state.get_int("health") {
+            Ok(health) => (100000.0 - health as f64) / 5.0,
+            Err(_) => 0.0,
         }
     }
 
+    fn goal(&self) -> Option<Goal> {
+        Some(Goal::new(vec![GoalCondition::AtLeast("health".to_string(), 100000.0)]))
+    }
+
+    fn goal_satisfaction(&self, state: &State) -> Option<GoalSatisfaction> {
+        Some(GoalSatisfaction { satisfied: usize::from(self.is_goal_state(state)), total: 1 })
+    }
+
 }
@@ -1,9 +1,8 @@
-use crate::search::{node::Node, state::State, action::Action, state::Value, state::Position};
-use std::collections::{BTreeMap, HashMap};
+use crate::search::{state::{State, StateError, ValueKind}, schema::StateSchema, action::Action, state::Value, goal::{Goal, GoalCondition, GoalSatisfaction}};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
 use crate::problems::problem::Problem;
-use serde_json::{to_string, Value as JsonValue};
-use std::collections::{HashMap as StdHashMap};
-use crate::problems::taxi_problem::taxi_problem::TaxiProblem;
+use serde_json::Value as JsonValue;
 
 
 pub struct WeightedSumGoal {
@@ -20,138 +19,139 @@ pub struct FarmProblem {
     pub weighted_sum_goal: WeightedSumGoal,          // Weighted sum goal
 }
 impl FarmProblem {
-    // Constructor that takes a path to a JSON file and loads the data
-    pub fn new_from_json(path: &str) -> Self {
+    // Loads an instance from a JSON file, failing on unreadable files and missing or mistyped entries
+    pub fn from_json(path: &str) -> Result<Self, Box<dyn Error>> {
         // Load the data from the JSON file
-        let file = std::fs::File::open(path).expect("File not found");
+        let file = std::fs::File::open(path).map_err(|error| format!("cannot open {}: {}", path, error))?;
         let reader = std::io::BufReader::new(file);
-        let json: serde_json::Value = serde_json::from_reader(reader).expect("Error reading JSON");
+        let json: JsonValue = serde_json::from_reader(reader)?;
+        Self::from_json_value(&json)
+    }
 
+    // Same as `from_json` for an instance already read
+    pub fn from_json_value(json: &JsonValue) -> Result<Self, Box<dyn Error>> {
         // Parse farms
-        let farms = json["farms"]
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|f| f.as_str().unwrap().to_string())
-            .collect();
+        let farms = strings(&json["farms"], "farms")?;
 
         // Parse x_values
-        let x_values = json["initial_state"]["x_values"].as_object().unwrap();
         let mut parsed_x_values = BTreeMap::new();
-        for (farm, x_val) in x_values {
-            parsed_x_values.insert(farm.clone(), x_val.as_i64().unwrap() as i32);
+        for (farm, x_val) in object(&json["initial_state"]["x_values"], "initial_state.x_values")? {
+            parsed_x_values.insert(farm.clone(), integer(x_val, farm)?);
+        }
+        // The state has a field per x value and the schema one per farm, so both must agree
+        if farms.iter().collect::<BTreeSet<_>>() != parsed_x_values.keys().collect() {
+            return Err("'farms' and 'initial_state.x_values' must name the same farms".into());
         }
 
         // Parse adjacencies
-        let adjacencies = json["initial_state"]["adjacencies"].as_object().unwrap();
         let mut parsed_adjacencies = BTreeMap::new();
-        for (farm, neighbors) in adjacencies {
-            let neighbors_vec = neighbors
-                .as_array()
-                .unwrap()
-                .iter()
-                .map(|n| n.as_str().unwrap().to_string())
-                .collect();
-            parsed_adjacencies.insert(farm.clone(), neighbors_vec);
+        for (farm, neighbors) in object(&json["initial_state"]["adjacencies"], "initial_state.adjacencies")? {
+            parsed_adjacencies.insert(farm.clone(), strings(neighbors, farm)?);
         }
 
         // Parse cost
-        let cost = json["initial_state"]["cost"].as_i64().unwrap() as i32;
+        let cost = integer(&json["initial_state"]["cost"], "initial_state.cost")?;
 
         // Parse goal thresholds
-        let goal_thresholds = json["goal"]["x_thresholds"].as_object().unwrap();
         let mut parsed_thresholds = BTreeMap::new();
-        for (farm, threshold) in goal_thresholds {
-            parsed_thresholds.insert(farm.clone(), threshold.as_i64().unwrap() as i32);
+        for (farm, threshold) in object(&json["goal"]["x_thresholds"], "goal.x_thresholds")? {
+            parsed_thresholds.insert(farm.clone(), integer(threshold, farm)?);
         }
 
         // Parse weighted sum goal
-        let weights = json["goal"]["weighted_sum_goal"]["weights"].as_object().unwrap();
+        let weighted = &json["goal"]["weighted_sum_goal"];
         let mut parsed_weights = BTreeMap::new();
-        for (farm, weight) in weights {
-            parsed_weights.insert(farm.clone(), weight.as_f64().unwrap());
+        for (farm, weight) in object(&weighted["weights"], "goal.weighted_sum_goal.weights")? {
+            parsed_weights.insert(farm.clone(), weight.as_f64().ok_or_else(|| invalid(farm, "a number"))?);
         }
         let weighted_sum_goal = WeightedSumGoal {
             weights: parsed_weights,
-            threshold: json["goal"]["weighted_sum_goal"]["threshold"].as_f64().unwrap(),
+            threshold: weighted["threshold"].as_f64().ok_or_else(|| invalid("goal.weighted_sum_goal.threshold", "a number"))?,
         };
 
         // Construct and return the FarmProblem instance
-        FarmProblem {
+        Ok(FarmProblem {
             farms,
             x_values: parsed_x_values,
             adjacencies: parsed_adjacencies,
             cost,
             goal_thresholds: parsed_thresholds,
             weighted_sum_goal,
-        }
+        })
     }
+}
 
-    pub fn create_initial_state(&self) -> State {
-        let mut state = State::new();
+fn invalid(entry: &str, expected: &str) -> Box<dyn Error> {
+    format!("'{}' is missing or not {}", entry, expected).into()
+}
 
-        // Insert x_values into the state
-        for (farm, x_val) in &self.x_values {
-            state.insert_field(farm.clone(), Value::Int(*x_val));
-        }
+fn object<'a>(json: &'a JsonValue, entry: &str) -> Result<&'a serde_json::Map<String, JsonValue>, Box<dyn Error>> {
+    json.as_object().ok_or_else(|| invalid(entry, "an object"))
+}
 
-        // Insert adjacencies into the state
-        let mut adjacency_map = BTreeMap::new();
-        for (farm, neighbors) in &self.adjacencies {
-            adjacency_map.insert(farm.clone(), neighbors.clone());
-        }
-        state.insert_field("adjacencies".to_string(), Value::MapToVecString(adjacency_map));
+fn integer(json: &JsonValue, entry: &str) -> Result<i32, Box<dyn Error>> {
+    json.as_i64().and_then(|value| i32::try_from(value).ok()).ok_or_else(|| invalid(entry, "a 32-bit integer"))
+}
 
-        // Insert goal thresholds into the state
-        let mut threshold_map = BTreeMap::new();
+fn strings(json: &JsonValue, entry: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let values = json.as_array().ok_or_else(|| invalid(entry, "a list of names"))?;
+    values
+        .iter()
+        .map(|value| value.as_str().map(str::to_string).ok_or_else(|| invalid(entry, "a list of names")))
+        .collect()
+}
+
+fn text_parameter<'a>(action: &'a Action, key: &str) -> Option<&'a str> {
+    action.parameters.get(key).and_then(Value::as_text)
+}
+
+impl FarmProblem {
+    // The weighted sum threshold is compared truncated to an integer, as when the state stored it
+    // as an `Int`
+    fn weighted_threshold(&self) -> f64 {
+        self.weighted_sum_goal.threshold as i32 as f64
+    }
+
+    // Sum of the amounts missing to each farm's threshold and to the weighted sum threshold; zero
+    // exactly for goal states
+    fn goal_distance(&self, state: &State) -> Result<f64, StateError> {
+        let mut distance = 0.0;
         for (farm, threshold) in &self.goal_thresholds {
-            threshold_map.insert(farm.clone(), *threshold);
+            distance += (threshold - state.get_int(farm)?).max(0) as f64;
         }
-        state.insert_field("goal_thresholds".to_string(), Value::MapToInt(threshold_map));
 
-        // Insert weighted sum goal into the state
-        let mut weighted_sum_map = BTreeMap::new();
+        let mut total_weighted_sum = 0.0;
         for (farm, weight) in &self.weighted_sum_goal.weights {
-            weighted_sum_map.insert(farm.clone(), weight.to_string()); // Convert float to string
+            total_weighted_sum += weight * state.get_int(farm)? as f64;
         }
-        state.insert_field("weighted_sum_goal".to_string(), Value::MapToString(weighted_sum_map));
-
-        state.insert_field("goal_threshold".to_string(), Value::Int(self.weighted_sum_goal.threshold as i32));
-
-        state
+        Ok(distance + (self.weighted_threshold() - total_weighted_sum).max(0.0))
     }
-
 }
 
-
 impl Problem for FarmProblem {
-    fn create_initial_node(&self, initial_state: State) -> Node {
-        Node {
-            state: initial_state,
-            parent: None,
-            children: Vec::new(),
-            action: None,
-            cost: 0,
+    type State = State;
+    type Action = Action;
+    type Cost = i32;
+
+    // Only the resources of the farms change, so they are the only fields of the state; the
+    // adjacencies and goal data stay on the problem
+    fn initial_state(&self) -> State {
+        let mut state = State::new();
+        for (farm, x_val) in &self.x_values {
+            state.set(farm, *x_val);
         }
+        state
     }
+
     fn get_possible_actions(&self, state: &State) -> Vec<Action> {
         let mut actions = Vec::new();
-
-        // Retrieve adjacencies from the state
-        let adjacencies = match state.get_field("adjacencies") {
-            Some(Value::MapToVecString(adjacencies)) => adjacencies,
-            _ => return actions, // If adjacencies are missing or of the wrong type, return no actions
-        };
-
-        // Retrieve x_values from the state
-        for (farm, _) in &self.x_values {
-            let x_value = match state.get_field(farm) {
-                Some(Value::Int(v)) => *v,
-                _ => continue, // Skip if x_value is missing or not an integer
+        for farm in self.x_values.keys() {
+            let Ok(x_value) = state.get_int(farm) else {
+                continue;
             };
 
             // Check neighbors for possible actions
-            if let Some(neighbors) = adjacencies.get(farm) {
+            if let Some(neighbors) = self.adjacencies.get(farm) {
                 for neighbor in neighbors {
                     if x_value >= 4 {
                         let mut params = HashMap::new();
@@ -171,182 +171,178 @@ impl Problem for FarmProblem {
                 }
             }
         }
-        println!("{:?}", actions);
         actions
     }
 
-
-
-
     fn apply_action(&self, state: &State, action: &Action) -> State {
-        let mut new_state = state.clone();
-
-        // Retrieve the parameters from the action using the updated keys
-        let farm0 = match action.parameters.get("farm0") {
-            Some(Value::Text(farm)) => farm.clone(),
-            _ => return new_state, // If farm0 is not present or not a string, return unchanged state
-        };
-
-        let farm1 = match action.parameters.get("farm1") {
-            Some(Value::Text(farm)) => farm.clone(),
-            _ => return new_state, // If farm1 is not present or not a string, return unchanged state
-        };
-
-        // Retrieve x_values for both farms
-        let x_farm0 = match new_state.get_field(&farm0) {
-            Some(Value::Int(v)) => *v,
-            _ => return new_state, // If x value for farm0 is missing, return unchanged state
+        let (Some(farm0), Some(farm1)) = (text_parameter(action, "farm0"), text_parameter(action, "farm1")) else {
+            return state.clone();
         };
-
-        let x_farm1 = match new_state.get_field(&farm1) {
-            Some(Value::Int(v)) => *v,
-            _ => return new_state, // If x value for farm1 is missing, return unchanged state
+        let (Ok(x_farm0), Ok(x_farm1)) = (state.get_int(farm0), state.get_int(farm1)) else {
+            return state.clone();
         };
 
         // Apply effects based on action type, ensuring x_farm0 stays non-negative
+        let mut new_state = state.clone();
         if action.name == "move-fast" && x_farm0 >= 4 {
-            new_state.insert_field(farm0.clone(), Value::Int(x_farm0 - 4));
-            new_state.insert_field(farm1.clone(), Value::Int(x_farm1 + 2));
-
-            if let Some(Value::Int(cost)) = new_state.get_field("cost") {
-                new_state.insert_field("cost".to_string(), Value::Int(cost + 1));
-            }
+            new_state.set(farm0, x_farm0 - 4);
+            new_state.set(farm1, x_farm1 + 2);
         } else if action.name == "move-slow" && x_farm0 >= 1 {
-            new_state.insert_field(farm0.clone(), Value::Int(x_farm0 - 1));
-            new_state.insert_field(farm1.clone(), Value::Int(x_farm1 + 1));
+            new_state.set(farm0, x_farm0 - 1);
+            new_state.set(farm1, x_farm1 + 1);
         }
-
         new_state
     }
 
-
-
-
-    fn is_goal_state(&self, state: &State) -> bool {
-        // Retrieve goal thresholds from the state
-        let goal_thresholds = match state.get_field("goal_thresholds") {
-            Some(Value::MapToInt(thresholds)) => thresholds,
-            _ => panic!("There was a problem: goal_thresholds missing or invalid"),
+    fn is_applicable(&self, state: &State, action: &Action) -> bool {
+        let (Some(farm0), Some(farm1)) = (text_parameter(action, "farm0"), text_parameter(action, "farm1")) else {
+            return false;
         };
 
-        // Check if all x_values meet or exceed their respective thresholds
-        for (farm, threshold) in goal_thresholds {
-            let x_value = match state.get_field(farm) {
-                Some(Value::Int(v)) => *v,
-                _ => panic!("There was a problem: missing x_value for farm {}", farm),
-            };
-            if x_value < *threshold {
-                return false; // If any farm's x_value is below the threshold, it's not a goal state
-            }
+        // farm1 must be a neighbor of farm0
+        let adjacent = self.adjacencies.get(farm0).is_some_and(|neighbors| neighbors.iter().any(|neighbor| neighbor == farm1));
+        if !adjacent || state.get_int(farm1).is_err() {
+            return false;
         }
 
-        // Retrieve weighted sum goal from the state
-        let weighted_sum_goal = match state.get_field("weighted_sum_goal") {
-            Some(Value::MapToString(weights)) => weights,
-            _ => panic!("There was a problem: weighted_sum_goal missing or invalid"),
+        let Ok(x_farm0) = state.get_int(farm0) else {
+            return false;
         };
+        match action.name.as_str() {
+            "move-fast" => x_farm0 >= 4,
+            "move-slow" => x_farm0 >= 1,
+            _ => false,
+        }
+    }
 
-        let mut total_weighted_sum = 0.0;
-        for (farm, weight_str) in weighted_sum_goal {
-            let weight = match weight_str.parse::<f64>() {
-                Ok(w) => w,
-                _ => panic!("There was a problem: parsing the weight for farm {}", farm),
-            };
+    // States with missing or mistyped farms, which only arise from states built by hand, are
+    // never goals and are treated as dead ends (infinite heuristic)
+    fn is_goal_state(&self, state: &State) -> bool {
+        self.goal_distance(state).is_ok_and(|distance| distance == 0.0)
+    }
 
-            let x_value = match state.get_field(farm) {
-                Some(Value::Int(v)) => *v as f64,
-                _ => panic!("There was a problem: missing x_value for farm {}", farm),
-            };
+    fn heuristic(&self, state: &State) -> f64 {
+        self.goal_distance(state).unwrap_or(f64::INFINITY)
+    }
 
-            total_weighted_sum += weight * x_value;
+    // Moves never create resources (a slow move keeps the total, a fast move loses 2), so once the
+    // thresholds are met the best any state can do is put all the surplus on the heaviest farm
+    fn is_dead_end(&self, state: &State) -> bool {
+        let weights = &self.weighted_sum_goal.weights;
+        if weights.values().any(|weight| *weight < 0.0) {
+            return false;
         }
 
-        // Retrieve the goal threshold for the weighted sum
-        let goal_threshold = match state.get_field("goal_threshold") {
-            Some(Value::Int(threshold)) => *threshold as f64,
-            _ => panic!("There was a problem: goal_threshold missing or invalid"),
-        };
+        let mut total = 0;
+        for farm in self.x_values.keys() {
+            match state.get_int(farm) {
+                Ok(v) => total += v,
+                Err(_) => return true,
+            }
+        }
+        let required: i32 = self.goal_thresholds.values().map(|threshold| (*threshold).max(0)).sum();
+        if total < required {
+            return true;
+        }
 
-        // Check if the total weighted sum meets or exceeds the goal threshold
-        total_weighted_sum >= goal_threshold
+        let weight_of = |farm: &String| weights.get(farm).copied().unwrap_or(0.0);
+        let max_weight = self.x_values.keys().map(weight_of).fold(0.0, f64::max);
+        let best_weighted_sum = self
+            .goal_thresholds
+            .iter()
+            .map(|(farm, threshold)| weight_of(farm) * (*threshold).max(0) as f64)
+            .sum::<f64>()
+            + max_weight * (total - required) as f64;
+        best_weighted_sum < self.weighted_threshold()
     }
 
-    fn heuristic(&self, state: &State) -> f64 {
-        let mut heuristic_value = 0.0;
-
-        // Retrieve goal thresholds
-        let goal_thresholds = match state.get_field("goal_thresholds") {
-            Some(Value::MapToInt(thresholds)) => thresholds,
-            _ => {
-                println!("Error: Goal thresholds are missing or invalid.");
-                std::process::exit(1);
-            }
-        };
-
-        // Calculate how far each farm's x_value is from the goal threshold
-        for (farm, threshold) in goal_thresholds {
-            let x_value = match state.get_field(farm) {
-                Some(Value::Int(v)) => *v,
-                _ => {
-                    println!("Error: x_value for farm {} is missing or invalid.", farm);
-                    std::process::exit(1);
-                }
-            };
+    fn goal(&self) -> Option<Goal> {
+        let mut conditions: Vec<GoalCondition> = self
+            .goal_thresholds
+            .iter()
+            .map(|(farm, threshold)| GoalCondition::AtLeast(farm.clone(), *threshold as f64))
+            .collect();
+        conditions.push(GoalCondition::WeightedSumAtLeast(
+            self.weighted_sum_goal.weights.clone(),
+            self.weighted_threshold(),
+        ));
+        Some(Goal::new(conditions))
+    }
 
-            if x_value < *threshold {
-                heuristic_value += (*threshold - x_value) as f64;
-            }
+    // The conditions of `goal`, counted without building it
+    fn goal_satisfaction(&self, state: &State) -> Option<GoalSatisfaction> {
+        let mut satisfied = self
+            .goal_thresholds
+            .iter()
+            .filter(|(farm, threshold)| state.get_int(farm).is_ok_and(|x_value| x_value >= **threshold))
+            .count();
+        let weighted_sum: Result<f64, StateError> =
+            self.weighted_sum_goal.weights.iter().map(|(farm, weight)| Ok(weight * state.get_int(farm)? as f64)).sum();
+        if weighted_sum.is_ok_and(|sum| sum >= self.weighted_threshold()) {
+            satisfied += 1;
         }
+        Some(GoalSatisfaction { satisfied, total: self.goal_thresholds.len() + 1 })
+    }
 
-        // Retrieve weighted sum goal
-        let weighted_sum_goal = match state.get_field("weighted_sum_goal") {
-            Some(Value::MapToString(weights)) => weights,
-            _ => {
-                println!("Error: Weighted sum goal is missing or invalid.");
-                std::process::exit(1);
-            }
-        };
+    // The resources of every farm, never negative as no move takes more than a farm has
+    fn schema(&self) -> Option<StateSchema> {
+        let schema = self
+            .x_values
+            .keys()
+            .fold(StateSchema::new(), |schema, farm| schema.ranged_field(farm, ValueKind::Int, 0.0, f64::INFINITY));
+        Some(schema)
+    }
 
-        let mut total_weighted_sum = 0.0;
-        for (farm, weight_str) in weighted_sum_goal {
-            let weight = match weight_str.parse::<f64>() {
-                Ok(w) => w,
-                _ => {
-                    println!("Error: Failed to parse weight for farm {}.", farm);
-                    std::process::exit(1);
-                }
-            };
+}
 
-            let x_value = match state.get_field(farm) {
-                Some(Value::Int(v)) => *v as f64,
-                _ => {
-                    println!("Error: x_value for farm {} is missing or invalid.", farm);
-                    std::process::exit(1);
-                }
-            };
 
-            total_weighted_sum += weight * x_value;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Retrieve the goal threshold for the weighted sum
-        let goal_threshold = match state.get_field("goal_threshold") {
-            Some(Value::Int(threshold)) => *threshold as f64,
-            _ => {
-                println!("Error: Goal threshold for weighted sum is missing or invalid.");
-                std::process::exit(1);
-            }
-        };
+    #[test]
+    fn test_dead_end_when_resources_cannot_reach_weighted_sum() {
+        let problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let mut state = problem.initial_state();
+        assert!(!problem.is_dead_end(&state));
 
-        // Calculate how far the weighted sum is from the goal threshold
-        if total_weighted_sum < goal_threshold {
-            heuristic_value += goal_threshold - total_weighted_sum;
-        }
-        // if heuristic_value < 150.0 {
-        //     println!("{:?}", state);
-        //     println!("Heuristic value: {}", heuristic_value);
-        // }
+        // Even with farm0's surplus moved to farm1, 2.7 + 1.7 * 399 < 840
+        state.insert_field("farm0".to_string(), Value::Int(400));
+        assert!(problem.is_dead_end(&state));
+        assert!(!problem.is_goal_state(&state));
+    }
+
+    #[test]
+    fn test_malformed_state_is_not_a_goal() {
+        let problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let mut state = problem.initial_state();
+        state.set("farm1", "1");
 
-        heuristic_value.max(0.0)
+        assert_eq!(problem.goal_distance(&state).unwrap_err().to_string(), "field 'farm1' is Text, expected Int");
+        assert!(!problem.is_goal_state(&state));
+        assert_eq!(problem.heuristic(&state), f64::INFINITY);
     }
 
+    #[test]
+    fn test_farms_must_match_x_values() {
+        let mut json: JsonValue = serde_json::from_str(&std::fs::read_to_string("inputs/farm_problem/small_input.json").unwrap()).unwrap();
+        json["farms"] = serde_json::json!(["farm0", "farm2"]);
+        let error = FarmProblem::from_json_value(&json).err().unwrap();
+        assert_eq!(error.to_string(), "'farms' and 'initial_state.x_values' must name the same farms");
+    }
+
+    #[test]
+    fn test_weighted_threshold_is_truncated() {
+        let mut problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        problem.weighted_sum_goal.threshold = 839.5;
+        let mut state = problem.initial_state();
+        // 834 + 1.7 * 3 = 839.1 reaches 839 but not 839.5
+        state.insert_field("farm0".to_string(), Value::Int(834));
+        state.insert_field("farm1".to_string(), Value::Int(3));
+
+        assert!(problem.is_goal_state(&state));
+        assert!(!problem.is_dead_end(&state));
+        assert!(problem.goal().unwrap().is_satisfied(&state));
+        assert_eq!(problem.goal_satisfaction(&state).unwrap().unsatisfied(), 0);
+    }
 }
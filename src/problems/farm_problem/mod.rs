@@ -1 +1,2 @@
-pub(crate) mod farm_problem;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod farm_problem;
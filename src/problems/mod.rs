@@ -1,4 +1,7 @@
 pub mod problem;
+pub mod stochastic_problem;
 pub mod example_problem;
-pub(crate) mod taxi_problem;
-pub(crate) mod farm_problem;
+pub mod taxi_problem;
+pub mod farm_problem;
+pub mod dyn_problem;
+pub mod registry;
@@ -1,10 +1,65 @@
-use crate::search::{node::Node, state::State, action::Action};
-use std::rc::Rc;
+use crate::search::{node::Node, action::SearchAction, cost::Cost, goal::{Goal, GoalSatisfaction}, schema::StateSchema};
+use std::fmt::Debug;
+use std::hash::Hash;
 
 pub trait Problem {
-    fn create_initial_node(&self, initial_state: State) -> Node;
-    fn get_possible_actions(&self, state: &State) -> Vec<Action>;
-    fn apply_action(&self, state: &State, action: &Action) -> State;
-    fn is_goal_state(&self, state: &State) -> bool;
-    fn heuristic(&self, state: &State) -> f64;
+    // Problems may use the generic map-based `search::state::State` or a compact state of their own.
+    // Every node stores, clones and hashes its state, so keep instance data that never changes
+    // (maps, adjacencies, goals, weights) on the problem and only the fluents in the state, as
+    // `FarmProblem` and `TaxiProblem` do.
+    type State: Clone + Eq + Hash + Debug;
+    // Likewise `search::action::Action` with i32 costs, or an action type of their own
+    type Action: SearchAction<Cost = Self::Cost>;
+    type Cost: Cost;
+
+    fn initial_state(&self) -> Self::State;
+    fn get_possible_actions(&self, state: &Self::State) -> Vec<Self::Action>;
+    fn apply_action(&self, state: &Self::State, action: &Self::Action) -> Self::State;
+    fn is_goal_state(&self, state: &Self::State) -> bool;
+    // May return f64::INFINITY for states from which no goal is reachable
+    fn heuristic(&self, state: &Self::State) -> f64;
+
+    fn create_initial_node(&self, initial_state: Self::State) -> Node<Self::State, Self::Action> {
+        Node::new_empty(initial_state)
+    }
+
+    // Whether no goal state can be reached from `state`. Searches prune such states, so it must
+    // never report a state from which a goal is reachable; the default never prunes.
+    fn is_dead_end(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    // Structured description of the goal, for problems over the map-based `State` whose goal can
+    // be expressed as field conditions. `is_goal_state` stays the authoritative goal test.
+    fn goal(&self) -> Option<Goal> {
+        None
+    }
+
+    // How many of its goal conditions `state` satisfies, for generic heuristics such as
+    // `goalcount`. Unlike `goal` it works with any state type; problems with a `goal` should count
+    // its conditions.
+    fn goal_satisfaction(&self, _state: &Self::State) -> Option<GoalSatisfaction> {
+        None
+    }
+
+    // Declared fields of the states of a problem over the map-based `State`, against which
+    // loaded initial states are validated (see `ProblemRegistry::load`)
+    fn schema(&self) -> Option<StateSchema> {
+        None
+    }
+
+    // Whether `action` can be applied in `state`. `apply_action` leaves the state unchanged for
+    // inapplicable actions, so use this (or `validate_plan`) to tell them apart.
+    fn is_applicable(&self, state: &Self::State, action: &Self::Action) -> bool {
+        self.get_possible_actions(state).contains(action)
+    }
+
+    // All applicable actions with the states they lead to. Override it when the successors
+    // can be generated in one pass more cheaply than through the two methods above.
+    fn successors(&self, state: &Self::State) -> impl Iterator<Item = (Self::Action, Self::State)> {
+        self.get_possible_actions(state).into_iter().map(move |action| {
+            let successor = self.apply_action(state, &action);
+            (action, successor)
+        })
+    }
 }
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use crate::config::registry::{farm_registry, simple_registry, taxi_registry};
+use crate::problems::dyn_problem::BoxedProblem;
+use crate::problems::example_problem::SimpleProblem;
+use crate::problems::farm_problem::farm_problem::FarmProblem;
+use crate::problems::problem::Problem;
+use crate::problems::taxi_problem::taxi_problem::TaxiProblem;
+use crate::search::state::State;
+
+// Loads an instance from a file, or the bundled instance of the problem if no file is given
+pub type ProblemFactory = fn(Option<&str>) -> Result<BoxedProblem, Box<dyn Error>>;
+
+// Problems that can be chosen by name, e.g. from the command line
+pub struct ProblemRegistry {
+    factories: BTreeMap<String, ProblemFactory>,
+}
+
+impl ProblemRegistry {
+    // An empty registry, for front ends offering only their own problems
+    pub fn empty() -> Self {
+        ProblemRegistry { factories: BTreeMap::new() }
+    }
+
+    // The bundled `taxi`, `farm` and `simple` problems
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register("taxi", |input| {
+            let problem = TaxiProblem::from_json(input.unwrap_or("inputs/taxi_problem/generated_input.json"))?;
+            Ok(BoxedProblem::with_registry(problem, taxi_registry))
+        });
+        registry.register("farm", |input| {
+            let problem = FarmProblem::from_json(input.unwrap_or("inputs/farm_problem/input.json"))?;
+            Ok(BoxedProblem::with_registry(problem, farm_registry))
+        });
+        registry.register("simple", |input| match input {
+            None => Ok(BoxedProblem::with_registry(SimpleProblem, simple_registry)),
+            Some(_) => Err("the simple problem takes no input file".into()),
+        });
+        registry
+    }
+
+    pub fn register(&mut self, name: &str, factory: ProblemFactory) {
+        self.factories.insert(name.to_string(), factory);
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.keys().map(String::as_str).collect()
+    }
+
+    // Load an instance, checking its initial state against the schema of the problem if it has one
+    pub fn load(&self, name: &str, input: Option<&str>) -> Result<BoxedProblem, Box<dyn Error>> {
+        let factory = self.factories.get(name).ok_or_else(|| {
+            format!("unknown problem '{}', expected one of: {}", name, self.names().join(", "))
+        })?;
+        let problem = factory(input)?;
+        if let (Some(schema), Some(state)) = (problem.schema(), problem.initial_state().downcast_ref::<State>()) {
+            if let Err(errors) = schema.validate(state) {
+                let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+                return Err(format!("invalid initial state: {}", messages.join("; ")).into());
+            }
+        }
+        Ok(problem)
+    }
+}
+
+impl Default for ProblemRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::problem::Problem;
+    use crate::search::solver::Solver;
+    use crate::search::validate_plan::validate_plan;
+
+    #[test]
+    fn test_boxed_problem_solves_like_the_typed_one() {
+        let registry = ProblemRegistry::new();
+        let problem = registry.load("farm", Some("inputs/farm_problem/small_input.json")).unwrap();
+        let configurations = problem.registry();
+        assert_eq!(configurations.heuristic_names(), vec!["default", "farm", "goalcount", "zero"]);
+
+        let boxed = Solver::new(&problem).configure(configurations.build("astar(h=goalcount)").unwrap()).solve();
+        let typed_problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let typed = Solver::new(&typed_problem).configure(farm_registry(&typed_problem).build("astar(h=goalcount)").unwrap()).solve();
+        let boxed_plan = boxed.plan.as_ref().unwrap();
+        assert!(validate_plan(&problem, &problem.initial_state(), boxed_plan).unwrap().is_valid());
+        assert!(validate_plan(&typed_problem, &typed_problem.initial_state(), typed.plan.as_ref().unwrap()).unwrap().is_valid());
+        assert_eq!(boxed.cost(), typed.cost());
+        assert_eq!(problem.initial_state(), problem.initial_state());
+        assert_eq!(serde_json::to_value(problem.initial_state()).unwrap(), serde_json::to_value(typed_problem.initial_state()).unwrap());
+        assert_ne!(problem.initial_state(), problem.apply_action(&problem.initial_state(), &boxed_plan[0]));
+
+        let error = registry.load("sokoban", None).err().unwrap().to_string();
+        assert_eq!(error, "unknown problem 'sokoban', expected one of: farm, simple, taxi");
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_errors() {
+        let registry = ProblemRegistry::new();
+        let error = registry.load("farm", Some("/nonexistent")).err().unwrap().to_string();
+        assert!(error.starts_with("cannot open /nonexistent"), "{}", error);
+
+        // Named after the process and time so that concurrent runs do not share the file
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!("registry_test_bad_bounds_{}_{}.json", std::process::id(), nanos));
+        std::fs::write(&path, r#"{"bounds": [3]}"#).unwrap();
+        let error = registry.load("taxi", path.to_str()).err().unwrap().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error, "bounds must be [width, height]");
+    }
+}
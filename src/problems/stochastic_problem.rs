@@ -0,0 +1,27 @@
+use rand::Rng;
+use crate::problems::problem::Problem;
+
+// A problem whose actions have probabilistic outcomes.
+// `Problem::apply_action` gives the intended (nominal) outcome, which keeps every deterministic
+// algorithm usable on the determinized problem.
+pub trait StochasticProblem: Problem {
+    // All possible successor states of applying `action` in `state`, with their probabilities summing to 1
+    fn get_action_outcomes(&self, state: &Self::State, action: &Self::Action) -> Vec<(Self::State, f64)>;
+
+    // Draw one successor state according to the outcome distribution
+    fn sample_action<R: Rng + ?Sized>(&self, state: &Self::State, action: &Self::Action, rng: &mut R) -> Self::State {
+        let mut outcomes = self.get_action_outcomes(state, action);
+        let mut sample = rng.gen::<f64>();
+        for (index, (_, probability)) in outcomes.iter().enumerate() {
+            if sample < *probability {
+                return outcomes.swap_remove(index).0;
+            }
+            sample -= probability;
+        }
+        // Rounding errors can leave a tiny remainder; attribute it to the last outcome
+        match outcomes.pop() {
+            Some((successor, _)) => successor,
+            None => state.clone(),
+        }
+    }
+}
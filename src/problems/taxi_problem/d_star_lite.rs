@@ -0,0 +1,289 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::search::{state::Position, action::Action};
+use crate::problems::taxi_problem::taxi_problem::TaxiProblem;
+
+const INFINITY: i32 = i32::MAX / 4;
+
+type Key = (i32, i32);
+
+// Incremental shortest paths for the taxi on a grid whose passability changes (D* Lite).
+// The search runs backwards from the goal and keeps its g/rhs values between queries, so after
+// tiles are added or removed only the affected part of the search is repaired.
+pub struct DStarLite {
+    width: i32,
+    height: i32,
+    impassable_tiles: HashSet<Position>,
+    start: Position,
+    goal: Position,
+    last_start: Position,
+    key_modifier: i32,
+    g: HashMap<Position, i32>,
+    rhs: HashMap<Position, i32>,
+    open: BTreeSet<(Key, (i32, i32))>,
+    open_keys: HashMap<Position, Key>,
+    pub expansions: usize, // Number of vertices expanded so far, over all queries
+}
+
+impl DStarLite {
+    pub fn new(problem: &TaxiProblem, start: Position, goal: Position) -> Self {
+        let mut search = DStarLite {
+            width: problem.width(),
+            height: problem.height(),
+            impassable_tiles: problem.impassable_tiles().clone(),
+            start,
+            goal,
+            last_start: start,
+            key_modifier: 0,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            open: BTreeSet::new(),
+            open_keys: HashMap::new(),
+            expansions: 0,
+        };
+        search.rhs.insert(goal, 0);
+        let key = search.calculate_key(&goal);
+        search.insert(goal, key);
+        search
+    }
+
+    // Shortest path from the current start to the goal, both included; None if the goal is unreachable
+    pub fn find_path(&mut self) -> Option<Vec<Position>> {
+        self.compute_shortest_path();
+        if self.g(&self.start) >= INFINITY {
+            return None;
+        }
+
+        let mut path = vec![self.start];
+        let mut current = self.start;
+        while current != self.goal {
+            current = self
+                .neighbors(&current)
+                .into_iter()
+                .min_by_key(|next| add_costs(self.cost(&current, next), self.g(next)))?;
+            path.push(current);
+        }
+        Some(path)
+    }
+
+    // The path as taxi move actions
+    pub fn find_actions(&mut self) -> Option<Vec<Action>> {
+        let path = self.find_path()?;
+        let actions = path
+            .windows(2)
+            .map(|step| {
+                let name = match (step[1].x - step[0].x, step[1].y - step[0].y) {
+                    (0, -1) => "move_up",
+                    (0, 1) => "move_down",
+                    (-1, 0) => "move_left",
+                    _ => "move_right",
+                };
+                Action::new(name.to_string(), 1, HashMap::new())
+            })
+            .collect();
+        Some(actions)
+    }
+
+    // Notify that the taxi has moved, e.g. after executing part of the path
+    pub fn update_start(&mut self, start: Position) {
+        self.key_modifier += heuristic(&self.last_start, &start);
+        self.last_start = start;
+        self.start = start;
+    }
+
+    // Notify that `tile` became impassable (or passable again)
+    pub fn update_tile(&mut self, tile: Position, impassable: bool) {
+        let changed = if impassable {
+            self.impassable_tiles.insert(tile)
+        } else {
+            self.impassable_tiles.remove(&tile)
+        };
+        if !changed {
+            return;
+        }
+        // Every edge into and out of the tile changed cost
+        self.update_vertex(tile);
+        for neighbor in self.neighbors(&tile) {
+            self.update_vertex(neighbor);
+        }
+    }
+
+    pub fn apply_to_problem(&self, problem: &mut TaxiProblem) {
+        problem.set_impassable_tiles(self.impassable_tiles.clone());
+    }
+
+    fn compute_shortest_path(&mut self) {
+        while let Some(&(old_key, (x, y))) = self.open.first() {
+            let start_key = self.calculate_key(&self.start);
+            if old_key >= start_key && self.rhs(&self.start) == self.g(&self.start) {
+                break;
+            }
+            let vertex = Position::new(x, y);
+            let new_key = self.calculate_key(&vertex);
+            self.expansions += 1;
+
+            if old_key < new_key {
+                self.remove(&vertex);
+                self.insert(vertex, new_key);
+            } else if self.g(&vertex) > self.rhs(&vertex) {
+                self.g.insert(vertex, self.rhs(&vertex));
+                self.remove(&vertex);
+                for predecessor in self.neighbors(&vertex) {
+                    self.update_vertex(predecessor);
+                }
+            } else {
+                self.g.insert(vertex, INFINITY);
+                self.update_vertex(vertex);
+                for predecessor in self.neighbors(&vertex) {
+                    self.update_vertex(predecessor);
+                }
+            }
+        }
+    }
+
+    fn update_vertex(&mut self, vertex: Position) {
+        if vertex != self.goal {
+            let rhs = self
+                .neighbors(&vertex)
+                .iter()
+                .map(|next| add_costs(self.cost(&vertex, next), self.g(next)))
+                .min()
+                .unwrap_or(INFINITY);
+            self.rhs.insert(vertex, rhs);
+        }
+        self.remove(&vertex);
+        if self.g(&vertex) != self.rhs(&vertex) {
+            let key = self.calculate_key(&vertex);
+            self.insert(vertex, key);
+        }
+    }
+
+    fn calculate_key(&self, vertex: &Position) -> Key {
+        let value = self.g(vertex).min(self.rhs(vertex));
+        (add_costs(add_costs(value, heuristic(&self.start, vertex)), self.key_modifier), value)
+    }
+
+    fn insert(&mut self, vertex: Position, key: Key) {
+        self.open.insert((key, (vertex.x, vertex.y)));
+        self.open_keys.insert(vertex, key);
+    }
+
+    fn remove(&mut self, vertex: &Position) {
+        if let Some(key) = self.open_keys.remove(vertex) {
+            self.open.remove(&(key, (vertex.x, vertex.y)));
+        }
+    }
+
+    fn g(&self, vertex: &Position) -> i32 {
+        self.g.get(vertex).copied().unwrap_or(INFINITY)
+    }
+
+    fn rhs(&self, vertex: &Position) -> i32 {
+        self.rhs.get(vertex).copied().unwrap_or(INFINITY)
+    }
+
+    fn cost(&self, from: &Position, to: &Position) -> i32 {
+        if self.impassable_tiles.contains(from) || self.impassable_tiles.contains(to) {
+            INFINITY
+        } else {
+            1
+        }
+    }
+
+    fn neighbors(&self, vertex: &Position) -> Vec<Position> {
+        [(0, -1), (0, 1), (-1, 0), (1, 0)]
+            .iter()
+            .map(|(dx, dy)| Position::new(vertex.x + dx, vertex.y + dy))
+            .filter(|pos| pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height)
+            .collect()
+    }
+}
+
+fn heuristic(from: &Position, to: &Position) -> i32 {
+    (from.x - to.x).abs() + (from.y - to.y).abs()
+}
+
+fn add_costs(a: i32, b: i32) -> i32 {
+    if a >= INFINITY || b >= INFINITY {
+        INFINITY
+    } else {
+        a + b
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::problem::Problem;
+    use crate::problems::taxi_problem::taxi_problem::small_taxi_problem;
+    use crate::search::validate_plan::validate_plan;
+
+    fn create_problem(impassable_tiles: &[Position]) -> TaxiProblem {
+        small_taxi_problem(6, 6, impassable_tiles, Position::new(0, 0), &[])
+    }
+
+    fn path_length(impassable_tiles: &[Position], start: Position, goal: Position) -> Option<usize> {
+        DStarLite::new(&create_problem(impassable_tiles), start, goal)
+            .find_path()
+            .map(|path| path.len() - 1)
+    }
+
+    #[test]
+    fn test_find_path_and_actions() {
+        let problem = create_problem(&[]);
+        let mut search = DStarLite::new(&problem, Position::new(0, 0), Position::new(2, 3));
+        let path = search.find_path().unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path[0], Position::new(0, 0));
+        assert_eq!(path[5], Position::new(2, 3));
+
+        let actions = search.find_actions().unwrap();
+        assert_eq!(actions.len(), 5);
+        assert_eq!(actions.iter().filter(|action| action.name == "move_down").count(), 3);
+        assert_eq!(actions.iter().filter(|action| action.name == "move_right").count(), 2);
+
+        // Without passengers every state is a goal, so this checks that the moves apply and end at the goal
+        let validation = validate_plan(&problem, &problem.initial_state(), &actions).unwrap();
+        assert!(validation.is_valid());
+        assert_eq!(validation.final_state.taxi, Position::new(2, 3));
+    }
+
+    #[test]
+    fn test_repairs_match_search_from_scratch() {
+        let start = Position::new(0, 0);
+        let goal = Position::new(5, 0);
+        let wall = [Position::new(3, 0), Position::new(3, 1), Position::new(3, 2), Position::new(3, 3), Position::new(3, 4)];
+
+        let mut search = DStarLite::new(&create_problem(&[]), start, goal);
+        assert_eq!(search.find_path().map(|path| path.len() - 1), Some(5));
+        let initial_expansions = search.expansions;
+
+        for tile in wall {
+            search.update_tile(tile, true);
+        }
+        assert_eq!(search.find_path().map(|path| path.len() - 1), path_length(&wall, start, goal));
+
+        // Close the gap: no path left
+        search.update_tile(Position::new(3, 5), true);
+        assert_eq!(search.find_path(), None);
+
+        // Reopening a tile next to the goal only needs a small repair
+        search.update_tile(Position::new(3, 0), false);
+        let before_repair = search.expansions;
+        assert_eq!(search.find_path().map(|path| path.len() - 1), Some(5));
+        assert!(search.expansions - before_repair <= initial_expansions);
+    }
+
+    #[test]
+    fn test_moving_start() {
+        let goal = Position::new(5, 5);
+        let wall = [Position::new(2, 2), Position::new(2, 3), Position::new(3, 2)];
+        let mut search = DStarLite::new(&create_problem(&wall), Position::new(0, 0), goal);
+        let path = search.find_path().unwrap();
+
+        search.update_start(path[3]);
+        search.update_tile(Position::new(4, 4), true);
+        let blocked = [Position::new(2, 2), Position::new(2, 3), Position::new(3, 2), Position::new(4, 4)];
+        assert_eq!(search.find_path().map(|path| path.len() - 1), path_length(&blocked, path[3], goal));
+    }
+}
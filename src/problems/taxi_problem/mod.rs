@@ -1,2 +1,5 @@
-pub(crate) mod taxi_problem;
-mod random_input_generator;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod taxi_problem;
+pub mod stochastic_taxi_problem;
+pub mod d_star_lite;
+pub mod random_input_generator;
@@ -1,42 +1,16 @@
-use clap::Parser;
 use rand::Rng;
 use serde_json::json;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::Write;
+use crate::problems::taxi_problem::taxi_problem::MAX_TILES;
 
-#[derive(Parser)]
-struct Args {
-    #[arg(short, long, help = "Width of the map")]
-    width: i32,
-
-    #[arg(long, help = "Height of the map")]
-    height: i32,
-
-    #[arg(short, long, help = "Number of passengers")]
-    passengers: usize,
-
-    #[arg(short, long, help = "Number of impassable tiles")]
-    impassable: usize,
-}
-
-fn main() {
-    let args = Args::parse();
-
-    match generate_input(args) {
-        Ok(json_data) => {
-            if let Err(e) = write_to_file(json_data) {
-                eprintln!("Error writing file: {}", e);
-            } else {
-                println!("Input JSON generated successfully: generated_input.json");
-            }
-        }
-        Err(e) => eprintln!("Error: {}", e),
+// Random taxi instance in the JSON format read by `load_state_from_json`
+pub fn generate_input(width: i32, height: i32, passenger_count: usize, impassable_count: usize) -> Result<String, String> {
+    if width <= 0 || height <= 0 {
+        return Err("Map width and height must be positive.".to_string());
     }
-}
-
-fn generate_input(args: Args) -> Result<String, String> {
-    if args.width * args.height <= (args.passengers * 2 + 1 + args.impassable) as i32 {
+    let tiles = width.checked_mul(height).filter(|tiles| *tiles as usize <= MAX_TILES).ok_or("Map size too large.")?;
+    let elements = passenger_count.checked_mul(2).and_then(|count| count.checked_add(1 + impassable_count));
+    if elements.is_none_or(|elements| tiles as usize <= elements) {
         return Err("Map size too small for the number of elements.".to_string());
     }
 
@@ -44,8 +18,8 @@ fn generate_input(args: Args) -> Result<String, String> {
     let mut positions: HashSet<(i32, i32)> = HashSet::new();
 
     let mut impassable_tiles = vec![];
-    while impassable_tiles.len() < args.impassable {
-        let pos = (rng.gen_range(0..args.width), rng.gen_range(0..args.height));
+    while impassable_tiles.len() < impassable_count {
+        let pos = (rng.gen_range(0..width), rng.gen_range(0..height));
         if positions.insert(pos) {
             impassable_tiles.push(pos);
         }
@@ -53,7 +27,7 @@ fn generate_input(args: Args) -> Result<String, String> {
 
     let mut taxi_position;
     loop {
-        taxi_position = (rng.gen_range(0..args.width), rng.gen_range(0..args.height));
+        taxi_position = (rng.gen_range(0..width), rng.gen_range(0..height));
         if !positions.contains(&taxi_position) {
             positions.insert(taxi_position);
             break;
@@ -61,18 +35,18 @@ fn generate_input(args: Args) -> Result<String, String> {
     }
 
     let mut passengers = vec![];
-    for i in 1..=args.passengers {
+    for i in 1..=passenger_count {
         let mut passenger_position;
         let mut goal_position;
         loop {
-            passenger_position = (rng.gen_range(0..args.width), rng.gen_range(0..args.height));
+            passenger_position = (rng.gen_range(0..width), rng.gen_range(0..height));
             if !positions.contains(&passenger_position) {
                 positions.insert(passenger_position);
                 break;
             }
         }
         loop {
-            goal_position = (rng.gen_range(0..args.width), rng.gen_range(0..args.height));
+            goal_position = (rng.gen_range(0..width), rng.gen_range(0..height));
             if !positions.contains(&goal_position) && goal_position != passenger_position {
                 positions.insert(goal_position);
                 break;
@@ -84,7 +58,7 @@ fn generate_input(args: Args) -> Result<String, String> {
 
     // Correct the JSON structure to wrap positions in serde_json::Value::Array
     let json_data = json!({
-        "bounds": [args.width, args.height],
+        "bounds": [width, height],
         "entities": {
             "taxi": [taxi_position.0, taxi_position.1],
             "passengers": passengers.iter().map(|(name, pos)| (name.clone(), serde_json::Value::Array(vec![pos.0.into(), pos.1.into()]))).collect::<serde_json::Map<_, _>>(),
@@ -95,8 +69,16 @@ fn generate_input(args: Args) -> Result<String, String> {
     Ok(json_data.to_string())
 }
 
-fn write_to_file(json_data: String) -> std::io::Result<()> {
-    let mut file = File::create("inputs/taxi_problem/generated_input.json")?;
-    write!(file, "{}", json_data)?;
-    Ok(())
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_sizes_are_errors() {
+        assert!(generate_input(-3, -4, 1, 0).is_err());
+        assert!(generate_input(70000, 70000, 1, 0).is_err());
+        assert!(generate_input(2, 2, 2, 0).is_err());
+        assert!(generate_input(5, 5, 2, 3).is_ok());
+    }
 }
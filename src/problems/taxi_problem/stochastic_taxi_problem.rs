@@ -0,0 +1,136 @@
+use crate::search::action::Action;
+use crate::problems::problem::Problem;
+use crate::problems::stochastic_problem::StochasticProblem;
+use crate::problems::taxi_problem::taxi_problem::{TaxiProblem, TaxiState};
+
+// Slippery taxi: every move fails with probability `failure_probability`, leaving the taxi in place.
+// Picking up and dropping off passengers always succeed.
+pub struct StochasticTaxiProblem {
+    pub taxi: TaxiProblem,
+    pub failure_probability: f64,
+}
+
+impl StochasticTaxiProblem {
+    pub fn new(taxi: TaxiProblem, failure_probability: f64) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&failure_probability) {
+            return Err(format!("failure probability must be within [0, 1], got {}", failure_probability));
+        }
+        Ok(StochasticTaxiProblem { taxi, failure_probability })
+    }
+}
+
+impl Problem for StochasticTaxiProblem {
+    type State = TaxiState;
+    type Action = Action;
+    type Cost = i32;
+
+    fn initial_state(&self) -> TaxiState {
+        self.taxi.initial_state()
+    }
+
+    fn get_possible_actions(&self, state: &TaxiState) -> Vec<Action> {
+        self.taxi.get_possible_actions(state)
+    }
+
+    fn apply_action(&self, state: &TaxiState, action: &Action) -> TaxiState {
+        self.taxi.apply_action(state, action)
+    }
+
+    fn is_goal_state(&self, state: &TaxiState) -> bool {
+        self.taxi.is_goal_state(state)
+    }
+
+    fn heuristic(&self, state: &TaxiState) -> f64 {
+        self.taxi.heuristic(state)
+    }
+
+    fn is_dead_end(&self, state: &TaxiState) -> bool {
+        self.taxi.is_dead_end(state)
+    }
+
+    fn is_applicable(&self, state: &TaxiState, action: &Action) -> bool {
+        self.taxi.is_applicable(state, action)
+    }
+
+    fn successors(&self, state: &TaxiState) -> impl Iterator<Item = (Action, TaxiState)> {
+        self.taxi.successors(state)
+    }
+}
+
+impl StochasticProblem for StochasticTaxiProblem {
+    fn get_action_outcomes(&self, state: &TaxiState, action: &Action) -> Vec<(TaxiState, f64)> {
+        let intended = self.taxi.apply_action(state, action);
+        let can_fail = action.name.starts_with("move") && self.failure_probability > 0.0;
+        if !can_fail || intended == *state {
+            return vec![(intended, 1.0)];
+        }
+        if self.failure_probability >= 1.0 {
+            return vec![(state.clone(), 1.0)];
+        }
+        vec![
+            (intended, 1.0 - self.failure_probability),
+            (state.clone(), self.failure_probability),
+        ]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::taxi_problem::taxi_problem::small_taxi_problem;
+    use crate::search::state::Position;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    fn create_problem(failure_probability: f64) -> (StochasticTaxiProblem, TaxiState) {
+        let taxi = small_taxi_problem(3, 1, &[], Position::new(0, 0), &[(Position::new(0, 0), Position::new(2, 0))]);
+        let state = taxi.initial_state();
+        (StochasticTaxiProblem::new(taxi, failure_probability).unwrap(), state)
+    }
+
+    #[test]
+    fn test_move_outcomes() {
+        let (problem, state) = create_problem(0.25);
+        let move_right = Action::new("move_right".to_string(), 1, HashMap::new());
+
+        let outcomes = problem.get_action_outcomes(&state, &move_right);
+
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0], (problem.apply_action(&state, &move_right), 0.75));
+        assert_eq!(outcomes[1], (state.clone(), 0.25));
+    }
+
+    #[test]
+    fn test_pick_up_is_deterministic() {
+        let (problem, state) = create_problem(0.25);
+        let pick_up = TaxiProblem::pick_up("passenger1");
+
+        let outcomes = problem.get_action_outcomes(&state, &pick_up);
+
+        assert_eq!(outcomes, vec![(problem.apply_action(&state, &pick_up), 1.0)]);
+    }
+
+    #[test]
+    fn test_sample_action_frequencies() {
+        let (problem, state) = create_problem(0.25);
+        let move_right = Action::new("move_right".to_string(), 1, HashMap::new());
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let failures = (0..4000)
+            .filter(|_| problem.sample_action(&state, &move_right, &mut rng) == state)
+            .count();
+
+        assert!((800..1200).contains(&failures), "unexpected failure count {}", failures);
+    }
+
+    #[test]
+    fn test_invalid_probabilities_are_errors() {
+        for failure_probability in [-0.1, 1.5, f64::NAN] {
+            let taxi = small_taxi_problem(3, 1, &[], Position::new(0, 0), &[]);
+            let error = StochasticTaxiProblem::new(taxi, failure_probability).err().unwrap();
+            assert_eq!(error, format!("failure probability must be within [0, 1], got {}", failure_probability));
+        }
+    }
+}
@@ -1,18 +1,19 @@
 use std::collections::BTreeMap;
 use std::collections::HashMap;
-use crate::search::{node::Node, state::State, action::Action, state::Value, state::Position};
+use crate::search::{action::Action, goal::GoalSatisfaction, state::{Position, Value}};
 use crate::problems::problem::Problem;
-use std::rc::Rc;
-// use std::collections::HashSet;
-use std::cmp::Ordering;
-use std::collections::{HashSet, BinaryHeap};
+use std::collections::HashSet;
+use std::sync::OnceLock;
 
 
 
 use std::fs::File;
 use std::io::BufReader;
 use serde_json::from_reader;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+// Largest map, in tiles, that `load_state_from_json` accepts
+pub const MAX_TILES: usize = 1 << 24;
 
 #[derive(Debug, Clone)]
 pub struct ProblemConfig {
@@ -22,35 +23,50 @@ pub struct ProblemConfig {
     pub goals: BTreeMap<String, Position>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Passenger {
+    pub position: Position, // Last position on the map; meaningless while in the taxi
+    pub in_taxi: bool,
+}
+
+// Compact taxi state. Passenger i is the passenger of the i-th goal of `TaxiProblem::goals`,
+// i.e. "goal2" belongs to "passenger2". Serialized as
+// `{"taxi": {"x": 0, "y": 1}, "passengers": [{"position": {"x": 3, "y": 0}, "in_taxi": false}]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaxiState {
+    pub taxi: Position,
+    pub passengers: Vec<Passenger>,
+}
+
 
-pub fn load_state_from_json(file_path: &str) -> Result<(State, ProblemConfig), Box<dyn std::error::Error>> {
+pub fn load_state_from_json(file_path: &str) -> Result<(TaxiState, ProblemConfig), Box<dyn std::error::Error>> {
     let file = File::open(file_path)?;
     let reader = BufReader::new(file);
     let raw_state: serde_json::Value = from_reader(reader)?;
+    parse_state_json(&raw_state)
+}
 
-    let bounds = raw_state["bounds"].as_array().unwrap();
-    let width = bounds[0].as_i64().unwrap() as i32;
-    let height = bounds[1].as_i64().unwrap() as i32;
+// Same as `load_state_from_json` for an instance already read
+pub fn parse_state_json(raw_state: &serde_json::Value) -> Result<(TaxiState, ProblemConfig), Box<dyn std::error::Error>> {
+    let bounds = raw_state["bounds"].as_array().ok_or("missing map bounds")?;
+    let (width, height) = match bounds.as_slice() {
+        [width, height] => (coordinate(width, "bounds")?, coordinate(height, "bounds")?),
+        _ => return Err("bounds must be [width, height]".into()),
+    };
+    let tiles = (width.max(0) as usize).checked_mul(height.max(0) as usize);
+    if tiles.is_none_or(|tiles| tiles > MAX_TILES) {
+        return Err(format!("map of {}x{} tiles is larger than {} tiles", width, height, MAX_TILES).into());
+    }
 
-    let entities = raw_state["entities"].as_object().unwrap();
+    let entities = raw_state["entities"].as_object().ok_or("missing entities")?;
     let mut positions_map = BTreeMap::new();
     let mut goals_map = BTreeMap::new();
 
-    if let Some(taxi) = entities.get("taxi") {
-        let taxi_coords = taxi.as_array().unwrap();
-        positions_map.insert(
-            "taxi".to_string(),
-            Position::new(
-                taxi_coords[0].as_i64().unwrap() as i32,
-                taxi_coords[1].as_i64().unwrap() as i32,
-            ),
-        );
-    }
+    let taxi = position(entities.get("taxi").ok_or("missing taxi position")?, "taxi")?;
 
-    let passengers = entities.get("passengers").unwrap().as_object().unwrap();
+    let passengers = entities.get("passengers").and_then(|value| value.as_object()).ok_or("missing passengers")?;
     for (key, value) in passengers {
-        let coords = value.as_array().unwrap();
-        let position = Position::new(coords[0].as_i64().unwrap() as i32, coords[1].as_i64().unwrap() as i32);
+        let position = position(value, key)?;
 
         if key.starts_with("goal") {
             goals_map.insert(key.clone(), position);
@@ -59,17 +75,21 @@ pub fn load_state_from_json(file_path: &str) -> Result<(State, ProblemConfig), B
         }
     }
 
-    let impassable_tiles = entities.get("impassable_tiles").unwrap().as_array().unwrap();
-    let impassable_positions: HashSet<Position> = impassable_tiles
+    let impassable_tiles = entities.get("impassable_tiles").and_then(|value| value.as_array()).ok_or("missing impassable tiles")?;
+    let impassable_positions = impassable_tiles
         .iter()
-        .map(|tile| {
-            let coords = tile.as_array().unwrap();
-            Position::new(coords[0].as_i64().unwrap() as i32, coords[1].as_i64().unwrap() as i32)
-        })
-        .collect();
-
-    let mut state = State::new();
-    state.insert_field("positions".to_string(), Value::Positions(positions_map));
+        .map(|tile| position(tile, "impassable_tiles"))
+        .collect::<Result<HashSet<Position>, _>>()?;
+
+    let mut passenger_states = Vec::new();
+    for goal_key in goals_map.keys() {
+        let passenger_key = goal_key.replace("goal", "passenger");
+        let position = positions_map
+            .get(&passenger_key)
+            .ok_or_else(|| format!("missing position for {}", passenger_key))?;
+        passenger_states.push(Passenger { position: *position, in_taxi: false });
+    }
+    let state = TaxiState { taxi, passengers: passenger_states };
 
     let config = ProblemConfig {
         width,
@@ -82,15 +102,121 @@ pub fn load_state_from_json(file_path: &str) -> Result<(State, ProblemConfig), B
 }
 
 
+fn coordinate(value: &serde_json::Value, entity: &str) -> Result<i32, Box<dyn std::error::Error>> {
+    value
+        .as_i64()
+        .and_then(|value| i32::try_from(value).ok())
+        .ok_or_else(|| format!("{} has a coordinate that is not a 32-bit integer", entity).into())
+}
+
+// An `[x, y]` pair
+fn position(value: &serde_json::Value, entity: &str) -> Result<Position, Box<dyn std::error::Error>> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([x, y]) => Ok(Position::new(coordinate(x, entity)?, coordinate(y, entity)?)),
+        _ => Err(format!("position of {} must be [x, y]", entity).into()),
+    }
+}
+
 pub struct TaxiProblem {
-    pub width: i32,
-    pub height: i32,
-    pub impassable_tiles: HashSet<Position>,
+    width: i32,
+    height: i32,
+    impassable_tiles: HashSet<Position>,
     pub goals: BTreeMap<String, Position>,
+    pub initial_state: TaxiState,
+    // For `is_dead_end`: computed on first use and reset whenever the tiles change; None if the
+    // map is too large to label
+    components: OnceLock<Option<TileComponents>>,
+}
+
+// Connected components of the grid
+struct TileComponents {
+    width: i32,
+    height: i32,
+    labels: Vec<Option<usize>>, // Component of every tile, row by row; None for impassable tiles
+}
+
+impl TileComponents {
+    fn new(width: i32, height: i32, impassable_tiles: &HashSet<Position>) -> Option<Self> {
+        let tiles = (width.max(0) as usize).checked_mul(height.max(0) as usize).filter(|tiles| *tiles <= MAX_TILES)?;
+        let mut components = TileComponents { width, height, labels: vec![None; tiles] };
+        let mut component = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let start = Position::new(x, y);
+                let Some(index) = components.index(&start) else { continue };
+                if impassable_tiles.contains(&start) || components.labels[index].is_some() {
+                    continue;
+                }
+                components.labels[index] = Some(component);
+                let mut frontier = vec![start];
+                while let Some(position) = frontier.pop() {
+                    for (_, next) in TaxiProblem::moves(&position) {
+                        let Some(index) = components.index(&next) else { continue };
+                        if !impassable_tiles.contains(&next) && components.labels[index].is_none() {
+                            components.labels[index] = Some(component);
+                            frontier.push(next);
+                        }
+                    }
+                }
+                component += 1;
+            }
+        }
+        Some(components)
+    }
+
+    fn index(&self, position: &Position) -> Option<usize> {
+        let within_bounds = position.x >= 0 && position.x < self.width && position.y >= 0 && position.y < self.height;
+        within_bounds.then(|| position.y as usize * self.width as usize + position.x as usize)
+    }
+
+    fn label(&self, position: &Position) -> Option<usize> {
+        self.labels[self.index(position)?]
+    }
 }
 
 impl TaxiProblem{
 
+    pub fn new(config: ProblemConfig, initial_state: TaxiState) -> Self {
+        TaxiProblem {
+            components: OnceLock::new(),
+            width: config.width,
+            height: config.height,
+            impassable_tiles: config.impassable_tiles,
+            goals: config.goals,
+            initial_state,
+        }
+    }
+
+    pub fn from_json(file_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (initial_state, config) = load_state_from_json(file_path)?;
+        Ok(TaxiProblem::new(config, initial_state))
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn impassable_tiles(&self) -> &HashSet<Position> {
+        &self.impassable_tiles
+    }
+
+    // Make `tile` impassable, or passable again
+    pub fn set_impassable(&mut self, tile: Position, impassable: bool) {
+        let changed = if impassable { self.impassable_tiles.insert(tile) } else { self.impassable_tiles.remove(&tile) };
+        if changed {
+            self.components.take();
+        }
+    }
+
+    pub fn set_impassable_tiles(&mut self, impassable_tiles: HashSet<Position>) {
+        self.components.take();
+        self.impassable_tiles = impassable_tiles;
+    }
+
     fn is_position_valid(&self, pos: &Position) -> bool {
         let within_bounds = pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height;
         if !within_bounds {
@@ -99,264 +225,387 @@ impl TaxiProblem{
         !self.impassable_tiles.contains(pos)
     }
 
+    // Move actions with the taxi position they lead to, including those leaving the grid
+    fn moves(taxi_pos: &Position) -> [(&'static str, Position); 5] {
+        [
+            ("move_up", Position::new(taxi_pos.x, taxi_pos.y - 1)),
+            ("move_down", Position::new(taxi_pos.x, taxi_pos.y + 1)),
+            ("move_left", Position::new(taxi_pos.x - 1, taxi_pos.y)),
+            ("move_right", Position::new(taxi_pos.x + 1, taxi_pos.y)),
+            ("stay", Position::new(taxi_pos.x, taxi_pos.y)),
+        ]
+    }
+
     fn manhattan_distance(pos1: &Position, pos2: &Position) -> f64 {
         (pos1.x - pos2.x).abs() as f64 + (pos1.y - pos2.y).abs() as f64
     }
+
+    // Names of the passengers, in the order of `TaxiState::passengers`
+    pub fn passenger_names(&self) -> Vec<String> {
+        self.goals.keys().map(|goal_key| goal_key.replace("goal", "passenger")).collect()
+    }
+
+    // Build a state from the taxi position and the waiting position of every passenger by name
+    pub fn create_state(&self, taxi: Position, passenger_positions: &BTreeMap<String, Position>) -> Result<TaxiState, String> {
+        let passengers = self
+            .passenger_names()
+            .iter()
+            .map(|name| {
+                let position = passenger_positions.get(name).ok_or_else(|| format!("missing position for {}", name))?;
+                Ok(Passenger { position: *position, in_taxi: false })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(TaxiState { taxi, passengers })
+    }
+
+    // `pick_up` and `disembark` name the passenger or goal they act on in a parameter, e.g.
+    // `pick_up passenger=passenger1`
+    pub fn pick_up(passenger_key: &str) -> Action {
+        Action::new("pick_up".to_string(), 1, HashMap::from([("passenger".to_string(), Value::Text(passenger_key.to_string()))]))
+    }
+
+    pub fn disembark(goal_key: &str) -> Action {
+        Action::new("disembark".to_string(), 1, HashMap::from([("goal".to_string(), Value::Text(goal_key.to_string()))]))
+    }
+
+    // The `key` parameter of an action named `name`
+    fn object<'a>(action: &'a Action, name: &str, key: &str) -> Option<&'a str> {
+        if action.name == name { action.parameters.get(key).and_then(Value::as_text) } else { None }
+    }
+
+    fn passenger_index(&self, passenger_key: &str) -> Option<usize> {
+        self.goals
+            .keys()
+            .position(|goal_key| goal_key.strip_prefix("goal") == passenger_key.strip_prefix("passenger"))
+    }
+}
+
+// Test instance with the taxi at `taxi` and passenger i + 1 waiting at `passengers[i].0` for
+// goal i + 1 at `passengers[i].1`
+#[cfg(test)]
+pub(crate) fn small_taxi_problem(
+    width: i32,
+    height: i32,
+    impassable_tiles: &[Position],
+    taxi: Position,
+    passengers: &[(Position, Position)],
+) -> TaxiProblem {
+    let goals = passengers.iter().enumerate().map(|(index, (_, goal))| (format!("goal{}", index + 1), *goal)).collect();
+    let passengers = passengers.iter().map(|(position, _)| Passenger { position: *position, in_taxi: false }).collect();
+    TaxiProblem::new(
+        ProblemConfig { width, height, impassable_tiles: impassable_tiles.iter().copied().collect(), goals },
+        TaxiState { taxi, passengers },
+    )
 }
 
 impl Problem for TaxiProblem {
-    fn create_initial_node(&self, initial_state: State) -> Node {
-        Node {
-            state: initial_state,
-            parent: None,
-            children: Vec::new(),
-            action: None,
-            cost: 0,
-        }
+    type State = TaxiState;
+    type Action = Action;
+    type Cost = i32;
+
+    fn initial_state(&self) -> TaxiState {
+        self.initial_state.clone()
     }
 
-    fn get_possible_actions(&self, state: &State) -> Vec<Action> {
+    fn get_possible_actions(&self, state: &TaxiState) -> Vec<Action> {
         let mut actions = Vec::new();
-        let positions = match state.get_field("positions") {
-            Some(Value::Positions(positions)) => positions,
-            _ => return actions,
-        };
-
-        let taxi_pos = match positions.get("taxi") {
-            Some(pos) => pos,
-            None => return actions,
-        };
-
-        let moves = vec![
-            ("move_up", Position::new(taxi_pos.x, taxi_pos.y - 1)),
-            ("move_down", Position::new(taxi_pos.x, taxi_pos.y + 1)),
-            ("move_left", Position::new(taxi_pos.x - 1, taxi_pos.y)),
-            ("move_right", Position::new(taxi_pos.x + 1, taxi_pos.y)),
-            ("stay", Position::new(taxi_pos.x, taxi_pos.y)),
-        ];
+        let taxi_pos = &state.taxi;
 
-        for (action_name, new_pos) in moves {
+        for (action_name, new_pos) in Self::moves(taxi_pos) {
             if self.is_position_valid(&new_pos) {
                 actions.push(Action::new(action_name.to_string(), 1, HashMap::new()));
             }
         }
 
-        for (key, pos) in positions.iter() {
-            if key.starts_with("passenger") && pos == taxi_pos {
-                actions.push(Action::new(
-                    format!("pick_up_{}", key),
-                    1,
-                    HashMap::new(),
-                ));
+        let names = self.passenger_names();
+        for (name, passenger) in names.iter().zip(&state.passengers) {
+            if !passenger.in_taxi && passenger.position == *taxi_pos {
+                actions.push(Self::pick_up(name));
             }
         }
 
-        for (goal_key, goal_pos) in self.goals.iter() {
-            let passenger_key = format!("in_taxi_{}", goal_key.replace("goal", "passenger"));
-            if goal_pos == taxi_pos && positions.contains_key(&passenger_key) {
-                actions.push(Action::new(
-                    format!("disembark_{}", goal_key),
-                    1,
-                    HashMap::new(),
-                ));
+        for ((goal_key, goal_pos), passenger) in self.goals.iter().zip(&state.passengers) {
+            if goal_pos == taxi_pos && passenger.in_taxi {
+                actions.push(Self::disembark(goal_key));
             }
         }
 
         actions
     }
 
-    fn apply_action(&self, state: &State, action: &Action) -> State {
+    fn apply_action(&self, state: &TaxiState, action: &Action) -> TaxiState {
         let mut new_state = state.clone();
-        let positions = match new_state.get_field("positions") {
-            Some(Value::Positions(positions)) => positions.clone(),
-            _ => return new_state,
-        };
-
-        let mut updated_positions = positions.clone();
+        let taxi_pos = state.taxi;
 
         if action.name.starts_with("move") {
-            let taxi_pos = positions.get("taxi").unwrap();
-            let new_taxi_pos = match action.name.as_str() {
+            new_state.taxi = match action.name.as_str() {
                 "move_up" => Position::new(taxi_pos.x, taxi_pos.y - 1),
                 "move_down" => Position::new(taxi_pos.x, taxi_pos.y + 1),
                 "move_left" => Position::new(taxi_pos.x - 1, taxi_pos.y),
                 "move_right" => Position::new(taxi_pos.x + 1, taxi_pos.y),
-                "stay" => taxi_pos.clone(),
-                _ => taxi_pos.clone(),
+                _ => taxi_pos,
             };
-            updated_positions.insert("taxi".to_string(), new_taxi_pos);
         }
 
-        if action.name.starts_with("pick_up") {
-            let passenger_key = action.name.replace("pick_up_", "");
-            if let Some(passenger_pos) = positions.get(&passenger_key) {
-                if let Some(taxi_pos) = positions.get("taxi") {
-                    if passenger_pos == taxi_pos {
-                        updated_positions.remove(&passenger_key);
-                        updated_positions.insert(format!("in_taxi_{}", passenger_key), taxi_pos.clone());
-                    }
+        if let Some(passenger_key) = Self::object(action, "pick_up", "passenger") {
+            if let Some(index) = self.passenger_index(passenger_key) {
+                let passenger = &mut new_state.passengers[index];
+                if !passenger.in_taxi && passenger.position == taxi_pos {
+                    passenger.in_taxi = true;
                 }
             }
         }
 
-        if action.name.starts_with("disembark") {
-            let goal_key = action.name.replace("disembark_", "");
-            let passenger_key = format!("in_taxi_{}", goal_key.replace("goal", "passenger"));
-
+        if let Some(goal_key) = Self::object(action, "disembark", "goal") {
             // Check if the passenger is in the taxi and the taxi is at the correct goal position
-            if let Some(goal_pos) = self.goals.get(&goal_key) {
-                if let Some(taxi_pos) = positions.get("taxi") {
-                    if taxi_pos == goal_pos && positions.contains_key(&passenger_key) {
-                        updated_positions.remove(&passenger_key);
-                        updated_positions.insert(
-                            passenger_key.replace("in_taxi_", ""),
-                            goal_pos.clone(),
-                        );
-                    }
+            if let Some(index) = self.goals.keys().position(|key| key == goal_key) {
+                let goal_pos = self.goals[goal_key];
+                let passenger = &mut new_state.passengers[index];
+                if taxi_pos == goal_pos && passenger.in_taxi {
+                    passenger.in_taxi = false;
+                    passenger.position = goal_pos;
                 }
             }
         }
 
-        new_state.insert_field("positions".to_string(), Value::Positions(updated_positions));
         new_state
     }
 
-    fn is_goal_state(&self, state: &State) -> bool {
-        let positions = match state.get_field("positions") {
-            Some(Value::Positions(positions)) => positions,
-            _ => {
-                println!("Failed to retrieve positions from state.");
-                return false;
-            }
-        };
+    fn is_applicable(&self, state: &TaxiState, action: &Action) -> bool {
+        let taxi_pos = state.taxi;
 
-        // Check if all goals have their corresponding passengers disembarked
-        for (goal_key, goal_pos) in &self.goals {
-            let passenger_key = goal_key.replace("goal", "passenger");
+        if let Some((_, new_pos)) = Self::moves(&taxi_pos).iter().find(|(name, _)| *name == action.name) {
+            return self.is_position_valid(new_pos);
+        }
 
-            // Check if the passenger is still in the taxi
-            if positions.contains_key(&format!("in_taxi_{}", passenger_key)) {
-                return false; // Passenger is still in the taxi
+        if let Some(passenger_key) = Self::object(action, "pick_up", "passenger") {
+            return self.passenger_index(passenger_key).is_some_and(|index| {
+                let passenger = &state.passengers[index];
+                !passenger.in_taxi && passenger.position == taxi_pos
+            });
+        }
+
+        if let Some(goal_key) = Self::object(action, "disembark", "goal") {
+            return self.goals.keys().position(|key| key == goal_key).is_some_and(|index| {
+                self.goals[goal_key] == taxi_pos && state.passengers[index].in_taxi
+            });
+        }
+
+        false
+    }
+
+    // Same actions as `get_possible_actions`, with the successor states built in the same pass
+    fn successors(&self, state: &TaxiState) -> impl Iterator<Item = (Action, TaxiState)> {
+        let mut successors = Vec::new();
+        let taxi_pos = state.taxi;
+
+        for (action_name, new_pos) in Self::moves(&taxi_pos) {
+            if self.is_position_valid(&new_pos) {
+                let mut new_state = state.clone();
+                new_state.taxi = new_pos;
+                successors.push((Action::new(action_name.to_string(), 1, HashMap::new()), new_state));
+            }
+        }
+
+        for (index, (goal_key, passenger)) in self.goals.keys().zip(&state.passengers).enumerate() {
+            if !passenger.in_taxi && passenger.position == taxi_pos {
+                let mut new_state = state.clone();
+                new_state.passengers[index].in_taxi = true;
+                successors.push((Self::pick_up(&goal_key.replace("goal", "passenger")), new_state));
             }
+        }
 
-            // Ensure the passenger is at the correct goal position
-            if let Some(pos) = positions.get(&passenger_key) {
-                if pos != goal_pos {
-                    return false; // Passenger is not at their goal position
-                }
-            } else {
-                return false; // Passenger is missing from positions
+        for (index, ((goal_key, goal_pos), passenger)) in self.goals.iter().zip(&state.passengers).enumerate() {
+            if *goal_pos == taxi_pos && passenger.in_taxi {
+                let mut new_state = state.clone();
+                new_state.passengers[index] = Passenger { position: *goal_pos, in_taxi: false };
+                successors.push((Self::disembark(goal_key), new_state));
             }
         }
 
-        println!("Goal state reached successfully.");
-        true // All passengers have been disembarked at their respective goals
+        successors.into_iter()
     }
 
-    // fn heuristic(&self, state: &State) -> f64 {
-    //     let positions = match state.get_field("positions") {
-    //         Some(Value::Positions(positions)) => positions,
-    //         _ => return f64::MAX,
-    //     };
-    //
-    //     let mut total_distance = 0.0;
-    //     let mut min_taxi_to_passenger_distance = f64::MAX;
-    //
-    //     for (goal_key, goal_pos) in &self.goals {
-    //         let passenger_key = goal_key.replace("goal", "passenger");
-    //
-    //         if positions.contains_key(&format!("in_taxi_{}", passenger_key)) {
-    //             // Passenger is in the taxi, add the distance to the goal + 1 for disembark
-    //             total_distance += Self::manhattan_distance(positions.get("taxi").unwrap(), goal_pos) + 1.0;
-    //         } else if let Some(passenger_pos) = positions.get(&passenger_key) {
-    //             // Passenger is not in the taxi and not at the goal, add the distance + 2 (for embark and disembark)
-    //             total_distance += Self::manhattan_distance(passenger_pos, goal_pos) + 2.0;
-    //
-    //             // Calculate the distance from the taxi to this passenger
-    //             if let Some(taxi_pos) = positions.get("taxi") {
-    //                 let taxi_to_passenger_distance = Self::manhattan_distance(taxi_pos, passenger_pos);
-    //                 // Track the minimum distance between the taxi and any passenger
-    //                 if taxi_to_passenger_distance < min_taxi_to_passenger_distance {
-    //                     min_taxi_to_passenger_distance = taxi_to_passenger_distance;
-    //                 }
-    //             }
-    //         }
-    //     }
-    //
-    //     // Add the minimal taxi-to-passenger distance to the total heuristic
-    //     total_distance += min_taxi_to_passenger_distance;
-    //
-    //     total_distance
-    // }
-
-
-
-    fn heuristic(&self, state: &State) -> f64 {
-        // Helper function to compute Manhattan distance between two positions
-        fn manhattan_distance(pos1: &Position, pos2: &Position) -> f64 {
-            ((pos1.x - pos2.x).abs() + (pos1.y - pos2.y).abs()) as f64
+    fn is_goal_state(&self, state: &TaxiState) -> bool {
+        // Check if all goals have their corresponding passengers disembarked
+        for (goal_pos, passenger) in self.goals.values().zip(&state.passengers) {
+            if passenger.in_taxi || passenger.position != *goal_pos {
+                return false; // Passenger is still in the taxi or not at their goal position
+            }
         }
 
-        // Extract positions from the state
-        let positions = match state.get_field("positions") {
-            Some(Value::Positions(positions)) => positions,
-            _ => return 0.0,
-        };
-
-        let taxi_pos = match positions.get("taxi") {
-            Some(pos) => pos,
-            None => return 0.0,
-        };
+        true // All passengers have been disembarked at their respective goals
+    }
 
-        let mut total_cost = 0.0;
+    // One condition per passenger: being at their goal, out of the taxi
+    fn goal_satisfaction(&self, state: &TaxiState) -> Option<GoalSatisfaction> {
+        let satisfied = self
+            .goals
+            .values()
+            .zip(&state.passengers)
+            .filter(|(goal_pos, passenger)| !passenger.in_taxi && passenger.position == **goal_pos)
+            .count();
+        Some(GoalSatisfaction { satisfied, total: self.goals.len() })
+    }
 
-        // Check if the taxi is carrying a passenger
-        let mut in_taxi_passenger: Option<String> = None;
-        for key in positions.keys() {
-            if key.starts_with("in_taxi_passenger") {
-                in_taxi_passenger = Some(key.clone());
-                break;
+    // A passenger still to be delivered whose position or goal the taxi cannot drive to
+    fn is_dead_end(&self, state: &TaxiState) -> bool {
+        if self.impassable_tiles.is_empty() {
+            return false;
+        }
+        let components = self.components.get_or_init(|| TileComponents::new(self.width, self.height, &self.impassable_tiles));
+        let Some(components) = components else { return false };
+        let taxi_component = components.label(&state.taxi);
+        let reachable = |position: &Position| taxi_component.is_some() && components.label(position) == taxi_component;
+        for (goal_pos, passenger) in self.goals.values().zip(&state.passengers) {
+            if !passenger.in_taxi && passenger.position == *goal_pos {
+                continue;
+            }
+            let picked_up = passenger.in_taxi || reachable(&passenger.position);
+            if !picked_up || !reachable(goal_pos) {
+                return true;
             }
         }
+        false
+    }
+
+    fn heuristic(&self, state: &TaxiState) -> f64 {
+        let taxi_pos = &state.taxi;
+        let mut total_cost = 0.0;
 
-        if let Some(passenger_in_taxi_key) = in_taxi_passenger {
+        // Check if the taxi is carrying a passenger
+        let in_taxi_goal = self
+            .goals
+            .values()
+            .zip(&state.passengers)
+            .find(|(_, passenger)| passenger.in_taxi)
+            .map(|(goal_pos, _)| goal_pos);
+
+        if let Some(goal_pos) = in_taxi_goal {
             // Taxi is carrying a passenger
-            let passenger_key = passenger_in_taxi_key.replace("in_taxi_", "");
-            let goal_key = passenger_key.replace("passenger", "goal");
-            let goal_pos = match self.goals.get(&goal_key) {
-                Some(pos) => pos,
-                None => return 0.0,
-            };
             // Distance from taxi's position to passenger's goal
-            total_cost += manhattan_distance(taxi_pos, goal_pos);
+            total_cost += Self::manhattan_distance(taxi_pos, goal_pos);
         } else {
             // Taxi is not carrying a passenger
-            // Find the minimal distance to any passenger
-            let mut min_pickup_cost = f64::INFINITY;
-            for (key, pos) in positions {
-                if key.starts_with("passenger") {
-                    let pickup_cost = manhattan_distance(taxi_pos, pos);
-                    if pickup_cost < min_pickup_cost {
-                        min_pickup_cost = pickup_cost;
-                    }
-                }
-            }
-            total_cost += min_pickup_cost;
+            // Find the minimal distance to any passenger still to be delivered, none if all are
+            let min_pickup_cost = self
+                .goals
+                .values()
+                .zip(&state.passengers)
+                .filter(|(goal_pos, passenger)| passenger.position != **goal_pos)
+                .map(|(_, passenger)| Self::manhattan_distance(taxi_pos, &passenger.position))
+                .reduce(f64::min);
+            total_cost += min_pickup_cost.unwrap_or(0.0);
         }
 
         // Sum distances from undelivered passengers to their goals
-        for (key, pos) in positions {
-            if key.starts_with("passenger") {
-                let goal_key = key.replace("passenger", "goal");
-                let goal_pos = match self.goals.get(&goal_key) {
-                    Some(pos) => pos,
-                    None => continue,
-                };
-                total_cost += manhattan_distance(pos, goal_pos);
+        for (goal_pos, passenger) in self.goals.values().zip(&state.passengers) {
+            if !passenger.in_taxi {
+                total_cost += Self::manhattan_distance(&passenger.position, goal_pos);
             }
         }
 
         total_cost
     }
 
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_successors_match_actions() {
+        let problem = small_taxi_problem(
+            3,
+            3,
+            &[Position::new(0, 1)],
+            Position::new(0, 0),
+            &[(Position::new(0, 0), Position::new(1, 1)), (Position::new(1, 1), Position::new(0, 0))],
+        );
+        let state = TaxiState {
+            taxi: Position::new(1, 1),
+            passengers: vec![
+                Passenger { position: Position::new(0, 0), in_taxi: true },
+                Passenger { position: Position::new(1, 1), in_taxi: false },
+            ],
+        };
+
+        let expected: Vec<_> = problem
+            .get_possible_actions(&state)
+            .into_iter()
+            .map(|action| {
+                let successor = problem.apply_action(&state, &action);
+                (action, successor)
+            })
+            .collect();
+        let successors: Vec<_> = problem.successors(&state).collect();
+
+        assert_eq!(successors, expected);
+        assert_eq!(successors.len(), 6);
+        assert!(successors.iter().all(|(action, _)| problem.is_applicable(&state, action)));
+        let blocked = Action::new("move_left".to_string(), 1, HashMap::new());
+        let wrong_goal = TaxiProblem::disembark("goal2");
+        assert!(!problem.is_applicable(&state, &blocked));
+        assert!(!problem.is_applicable(&state, &wrong_goal));
+
+        // Disembarking passenger1 at goal1 satisfies one of the two goal conditions
+        assert_eq!(problem.goal_satisfaction(&state), Some(GoalSatisfaction { satisfied: 0, total: 2 }));
+        let delivered = problem.apply_action(&state, &TaxiProblem::disembark("goal1"));
+        assert_eq!(problem.goal_satisfaction(&delivered), Some(GoalSatisfaction { satisfied: 1, total: 2 }));
+    }
+
+    #[test]
+    fn test_dead_ends_follow_tile_changes() {
+        let mut problem =
+            small_taxi_problem(3, 2, &[Position::new(1, 0)], Position::new(0, 0), &[(Position::new(2, 0), Position::new(0, 1))]);
+        let state = problem.initial_state();
+        assert!(!problem.is_dead_end(&state));
+
+        // Walling off the passenger's column makes the state a dead end, reopening it undoes that
+        problem.set_impassable(Position::new(1, 1), true);
+        assert!(problem.is_dead_end(&state));
+        problem.set_impassable(Position::new(1, 0), false);
+        assert!(!problem.is_dead_end(&state));
+    }
+
+    #[test]
+    fn test_problems_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<TaxiProblem>();
+        assert_send_sync::<TaxiState>();
+    }
+
+    #[test]
+    fn test_no_passengers_left() {
+        // Nothing is left to do, so the heuristic is zero rather than infinite (a dead end)
+        let problem = small_taxi_problem(3, 2, &[], Position::new(2, 1), &[(Position::new(0, 1), Position::new(0, 1))]);
+        assert_eq!(problem.heuristic(&problem.initial_state()), 0.0);
+        let empty = small_taxi_problem(2, 2, &[], Position::new(1, 1), &[]);
+        assert_eq!(empty.heuristic(&empty.initial_state()), 0.0);
+
+        let positions = BTreeMap::from([("passenger2".to_string(), Position::new(1, 1))]);
+        assert_eq!(problem.create_state(Position::new(0, 0), &positions).unwrap_err(), "missing position for passenger1");
+    }
+
+    #[test]
+    fn test_oversized_maps() {
+        let raw_state = serde_json::json!({
+            "bounds": [70000, 70000],
+            "entities": {"taxi": [0, 0], "passengers": {}, "impassable_tiles": []},
+        });
+        let error = parse_state_json(&raw_state).unwrap_err();
+        assert_eq!(error.to_string(), format!("map of 70000x70000 tiles is larger than {} tiles", MAX_TILES));
+
+        // Built directly, such a map is never labelled, so no state is taken for a dead end
+        let problem = small_taxi_problem(
+            70000,
+            70000,
+            &[Position::new(1, 0)],
+            Position::new(0, 0),
+            &[(Position::new(2, 0), Position::new(69999, 69999))],
+        );
+        assert!(!problem.is_dead_end(&problem.initial_state()));
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::search::state::State;
+use crate::problems::problem::Problem;
+
+// Heuristic values learned by real-time search. States that were never updated fall back
+// to `Problem::heuristic`, so the table can be kept across trials to let them converge.
+#[derive(Debug, Clone)]
+pub struct HeuristicTable<S = State> {
+    values: HashMap<S, f64>,
+}
+
+impl<S: Clone + Eq + Hash> HeuristicTable<S> {
+    pub fn new() -> Self {
+        HeuristicTable {
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn get<P: Problem<State = S>>(&self, problem: &P, state: &S) -> f64 {
+        match self.values.get(state) {
+            Some(value) => *value,
+            None => problem.heuristic(state),
+        }
+    }
+
+    pub fn update(&mut self, state: S, value: f64) {
+        self.values.insert(state, value);
+    }
+
+    pub fn learned_values(&self) -> &HashMap<S, f64> {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<S: Clone + Eq + Hash> Default for HeuristicTable<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::search::action::SearchAction;
+use crate::search::cost::Cost;
+use crate::problems::problem::Problem;
+use crate::realtime::heuristic_table::HeuristicTable;
+use crate::realtime::real_time_search::RealTimeSearch;
+
+// Learning Real-Time A*: a depth-limited minimin lookahead, after which the heuristic value of
+// the current state is raised to the best lookahead value. A depth of 1 is the classic LRTA*.
+pub struct LrtaStar {
+    pub lookahead_depth: usize,
+}
+
+impl LrtaStar {
+    pub fn new(lookahead_depth: usize) -> Self {
+        assert!(lookahead_depth >= 1, "LRTA* needs a lookahead depth of at least 1");
+        LrtaStar { lookahead_depth }
+    }
+
+    // Minimin value of `state` searched `depth` levels deep. Interior states of the lookahead
+    // also learn their backed-up value, otherwise repeated lookaheads could keep returning the
+    // same stale frontier values and the agent could loop forever.
+    fn lookahead<P: Problem>(problem: &P, table: &mut HeuristicTable<P::State>, state: &P::State, depth: usize) -> f64 {
+        if problem.is_goal_state(state) {
+            return 0.0;
+        }
+        if depth == 0 {
+            return table.get(problem, state);
+        }
+        let mut value = f64::INFINITY;
+        for (action, successor) in problem.successors(state) {
+            value = value.min(action.cost().to_f64() + Self::lookahead(problem, table, &successor, depth - 1));
+        }
+        Self::learn(problem, table, state, value)
+    }
+
+    // Learned values never decrease, so an admissible heuristic stays admissible
+    fn learn<P: Problem>(problem: &P, table: &mut HeuristicTable<P::State>, state: &P::State, value: f64) -> f64 {
+        let learned = table.get(problem, state).max(value);
+        table.update(state.clone(), learned);
+        learned
+    }
+}
+
+impl RealTimeSearch for LrtaStar {
+    fn select_action<P: Problem>(&self, problem: &P, table: &mut HeuristicTable<P::State>, state: &P::State) -> Option<P::Action> {
+        let mut best: Option<(P::Action, f64)> = None;
+        for (action, successor) in problem.successors(state) {
+            let value = action.cost().to_f64() + Self::lookahead(problem, table, &successor, self.lookahead_depth - 1);
+            if best.as_ref().is_none_or(|(_, best_value)| value < *best_value) {
+                best = Some((action, value));
+            }
+        }
+
+        let (action, value) = best?;
+        Self::learn(problem, table, state, value);
+        Some(action)
+    }
+}
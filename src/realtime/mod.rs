@@ -0,0 +1,52 @@
+pub mod heuristic_table;
+pub mod real_time_search;
+pub mod lrta_star;
+pub mod rtaa_star;
+
+
+#[cfg(test)]
+mod tests {
+    use crate::problems::problem::Problem;
+    use crate::problems::taxi_problem::taxi_problem::{small_taxi_problem, TaxiProblem, TaxiState};
+    use crate::realtime::heuristic_table::HeuristicTable;
+    use crate::realtime::lrta_star::LrtaStar;
+    use crate::realtime::real_time_search::RealTimeSearch;
+    use crate::realtime::rtaa_star::RtaaStar;
+    use crate::search::state::Position;
+    use crate::search::validate_plan::validate_plan;
+
+    // The wall forces a detour the Manhattan heuristic does not see; the optimal plan costs 8
+    fn create_problem() -> (TaxiProblem, TaxiState) {
+        let walls = [Position::new(1, 0), Position::new(1, 1)];
+        let problem = small_taxi_problem(3, 3, &walls, Position::new(0, 0), &[(Position::new(0, 0), Position::new(2, 0))]);
+        let state = problem.initial_state();
+        (problem, state)
+    }
+
+    fn converged_cost<S: RealTimeSearch>(search: &S) -> i32 {
+        let (problem, state) = create_problem();
+        let mut table = HeuristicTable::new();
+        let mut costs = Vec::new();
+        for _ in 0..30 {
+            let (actions, cost) = search.run_trial(&problem, &mut table, &state, 1000).unwrap();
+            let validation = validate_plan(&problem, &state, &actions).unwrap();
+            assert!(validation.is_valid());
+            assert_eq!(validation.cost, cost);
+            costs.push(cost);
+        }
+        assert!(!table.is_empty());
+        *costs.last().unwrap()
+    }
+
+    #[test]
+    fn test_lrta_star_trials_converge() {
+        assert_eq!(converged_cost(&LrtaStar::new(1)), 8);
+        assert_eq!(converged_cost(&LrtaStar::new(3)), 8);
+    }
+
+    #[test]
+    fn test_rtaa_star_trials_converge() {
+        assert_eq!(converged_cost(&RtaaStar::new(1)), 8);
+        assert_eq!(converged_cost(&RtaaStar::new(5)), 8);
+    }
+}
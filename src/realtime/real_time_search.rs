@@ -0,0 +1,36 @@
+use crate::search::action::SearchAction;
+use crate::search::cost::Cost;
+use crate::problems::problem::Problem;
+use crate::realtime::heuristic_table::HeuristicTable;
+
+// A real-time search algorithm performs a bounded lookahead from the current state, updates the
+// learned heuristic values and commits to a single action
+pub trait RealTimeSearch {
+    // Choose the action to execute in `state`; None if the state has no applicable action
+    fn select_action<P: Problem>(&self, problem: &P, table: &mut HeuristicTable<P::State>, state: &P::State) -> Option<P::Action>;
+
+    // Repeatedly select and execute actions from `initial_state` until the goal is reached.
+    // Passing the same table to consecutive trials lets the solution cost converge.
+    fn run_trial<P: Problem>(
+        &self,
+        problem: &P,
+        table: &mut HeuristicTable<P::State>,
+        initial_state: &P::State,
+        max_steps: usize,
+    ) -> Result<(Vec<P::Action>, P::Cost), &'static str> {
+        let mut state = initial_state.clone();
+        let mut actions = Vec::new();
+        let mut cost = P::Cost::zero();
+
+        while !problem.is_goal_state(&state) {
+            if actions.len() >= max_steps {
+                return Err("Step limit reached before the goal");
+            }
+            let action = self.select_action(problem, table, &state).ok_or("No solution found")?;
+            state = problem.apply_action(&state, &action);
+            cost = cost.checked_add(action.cost()).ok_or("Plan cost overflow")?;
+            actions.push(action);
+        }
+        Ok((actions, cost))
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+use crate::search::cost::Cost;
+use crate::search::search_tree::SearchTree;
+use crate::problems::problem::Problem;
+use crate::algorithms::astar::AStarQueue;
+use crate::algorithms::priority_queue::PriorityQueue;
+use crate::realtime::heuristic_table::HeuristicTable;
+use crate::realtime::real_time_search::RealTimeSearch;
+
+// Real-Time Adaptive A*: an A* search limited to `expansions` node expansions. Every expanded
+// state s then learns h(s) = max(h(s), f(best frontier node) - g(s)), and the first action on the path
+// to the best frontier node is executed.
+pub struct RtaaStar {
+    pub expansions: usize,
+}
+
+impl RtaaStar {
+    pub fn new(expansions: usize) -> Self {
+        assert!(expansions >= 1, "RTAA* needs at least one expansion");
+        RtaaStar { expansions }
+    }
+}
+
+impl RealTimeSearch for RtaaStar {
+    fn select_action<P: Problem>(&self, problem: &P, table: &mut HeuristicTable<P::State>, state: &P::State) -> Option<P::Action> {
+        let mut tree = SearchTree::new(state.clone());
+        let mut queue = AStarQueue::new();
+        queue.insert(0, 0, table.get(problem, state));
+        let mut closed_states = HashSet::new();
+        let mut closed_nodes = Vec::new();
+
+        let frontier_index = loop {
+            let current_index = PriorityQueue::<P::Cost>::pop(&mut queue)?;
+            let current_state = &tree.get_node(current_index).unwrap().state;
+            if closed_states.contains(current_state) {
+                continue;
+            }
+            if problem.is_goal_state(current_state) || closed_nodes.len() >= self.expansions {
+                break current_index;
+            }
+            closed_states.insert(current_state.clone());
+            closed_nodes.push(current_index);
+
+            let successors = tree.expand_node_with_successors(current_index, |state| problem.successors(state).collect()).ok()?;
+            for successor_index in successors {
+                let successor_node = tree.get_node(successor_index).unwrap();
+                queue.insert(successor_index, successor_node.cost, table.get(problem, &successor_node.state));
+            }
+        };
+
+        let frontier_node = tree.get_node(frontier_index).unwrap();
+        let frontier_f = frontier_node.cost.to_f64() + table.get(problem, &frontier_node.state);
+        for node_index in closed_nodes {
+            let node = tree.get_node(node_index).unwrap();
+            let learned = table.get(problem, &node.state).max(frontier_f - node.cost.to_f64());
+            table.update(node.state.clone(), learned);
+        }
+
+        tree.trace_actions(frontier_index).into_iter().next()
+    }
+}
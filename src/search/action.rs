@@ -1,19 +1,65 @@
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
 use super::state::Value;
+use super::cost::Cost;
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Clone, PartialEq)]
+// Actions produced by a problem; `Action` is the generic implementation, but problems may define their own
+pub trait SearchAction: Clone + Debug + PartialEq {
+    type Cost: Cost;
+
+    fn cost(&self) -> Self::Cost;
+    fn name(&self) -> String;
+
+    // Identifies the action when learning per-action values (e.g. NRPA policies); equal actions share a code
+    fn code(&self) -> String {
+        self.name()
+    }
+}
+
+// Serialized as `{"name": "move-slow", "cost": 1, "parameters": {"farm0": {"Text": "farm2"}}}`,
+// with the parameters in key order and values as described in `search::state`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Action {
     pub name: String,
     pub cost: i32,
+    #[serde(serialize_with = "serialize_sorted", default)]
     pub parameters: HashMap<String, Value>,
 }
 
+fn serialize_sorted<S: Serializer>(parameters: &HashMap<String, Value>, serializer: S) -> Result<S::Ok, S::Error> {
+    parameters.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
 impl Action {
     pub fn new(name: String, cost: i32, parameters: HashMap<String, Value>) -> Self {
         Action {name, cost, parameters}
     }
 }
 
+impl SearchAction for Action {
+    type Cost = i32;
+
+    fn cost(&self) -> i32 {
+        self.cost
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    // The name followed by the parameters, sorted by key
+    fn code(&self) -> String {
+        let mut parameters: Vec<_> = self.parameters.iter().collect();
+        parameters.sort_by_key(|(key, _)| *key);
+        let mut code = self.name.clone();
+        for (key, value) in parameters {
+            code.push_str(&format!(" {}={:?}", key, value));
+        }
+        code
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -32,6 +78,28 @@ mod tests {
         assert_eq!(action.parameters.get("speed"), Some(&Value::Int(10)));
         assert_eq!(action.parameters.get("direction"), Some(&Value::Text("north".to_string())));
     }
+
+    #[test]
+    fn test_action_code_sorts_parameters() {
+        let mut parameters = HashMap::new();
+        parameters.insert("farm1".to_string(), Value::Text("b".to_string()));
+        parameters.insert("farm0".to_string(), Value::Text("a".to_string()));
+        let action = Action::new("move-slow".to_string(), 1, parameters);
+        assert_eq!(action.code(), "move-slow farm0=Text(\"a\") farm1=Text(\"b\")");
+    }
+
+    #[test]
+    fn test_action_json_round_trip() {
+        let mut parameters = HashMap::new();
+        parameters.insert("farm1".to_string(), Value::Text("b".to_string()));
+        parameters.insert("farm0".to_string(), Value::Text("a".to_string()));
+        let action = Action::new("move-slow".to_string(), 2, parameters);
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json, r#"{"name":"move-slow","cost":2,"parameters":{"farm0":{"Text":"a"},"farm1":{"Text":"b"}}}"#);
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), action);
+        assert_eq!(serde_json::from_str::<Action>(r#"{"name":"wait","cost":1}"#).unwrap().parameters, HashMap::new());
+    }
 }
 
 
@@ -0,0 +1,136 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::ops::Add;
+use serde::{Deserialize, Serialize};
+
+// Numeric type used for action and path costs
+pub trait Cost: Copy + PartialOrd + Debug {
+    fn zero() -> Self;
+    // None if the sum overflows (integers) or is not a finite number (floats)
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn to_f64(self) -> f64;
+}
+
+impl Cost for i32 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i32::checked_add(self, other)
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Cost for i64 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i64::checked_add(self, other)
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Cost for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let sum = self + other;
+        if sum.is_finite() {
+            Some(sum)
+        } else {
+            None
+        }
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+// f64 with a total order, so it can be used where Ord, Eq or Hash are required. Serialized as
+// the plain number.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OrderedFloat(pub f64);
+
+impl PartialEq for OrderedFloat {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedFloat {}
+
+impl PartialOrd for OrderedFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedFloat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for OrderedFloat {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl Add for OrderedFloat {
+    type Output = OrderedFloat;
+
+    fn add(self, other: Self) -> Self::Output {
+        OrderedFloat(self.0 + other.0)
+    }
+}
+
+impl Cost for OrderedFloat {
+    fn zero() -> Self {
+        OrderedFloat(0.0)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(OrderedFloat)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(Cost::checked_add(i32::MAX - 1, 1), Some(i32::MAX));
+        assert_eq!(Cost::checked_add(i32::MAX, 1), None);
+        assert_eq!(Cost::checked_add(i64::MAX, 1i64), None);
+        assert_eq!(Cost::checked_add(1.5, 2.0), Some(3.5));
+        assert_eq!(Cost::checked_add(f64::MAX, f64::MAX), None);
+        assert_eq!(OrderedFloat(1.0).checked_add(OrderedFloat(f64::NAN)), None);
+    }
+
+    #[test]
+    fn test_ordered_float_total_order() {
+        let mut values = vec![OrderedFloat(2.5), OrderedFloat(-1.0), OrderedFloat(0.5)];
+        values.sort();
+        assert_eq!(values, vec![OrderedFloat(-1.0), OrderedFloat(0.5), OrderedFloat(2.5)]);
+    }
+}
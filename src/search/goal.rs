@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use crate::search::state::{State, Value};
+
+// A single condition on the fields of a `State`
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoalCondition {
+    Equals(String, Value),                          // The field holds exactly this value
+    AtLeast(String, f64),                           // The numeric field is >= the bound
+    AtMost(String, f64),                            // The numeric field is <= the bound
+    WeightedSumAtLeast(BTreeMap<String, f64>, f64), // sum(weight * field) >= the bound
+}
+
+impl GoalCondition {
+    // Missing or non-numeric fields never satisfy a numeric condition
+    pub fn is_satisfied(&self, state: &State) -> bool {
+        match self {
+            GoalCondition::Equals(field, value) => state.get_field(field) == Some(value),
+            GoalCondition::AtLeast(field, bound) => numeric_field(state, field).is_some_and(|v| v >= *bound),
+            GoalCondition::AtMost(field, bound) => numeric_field(state, field).is_some_and(|v| v <= *bound),
+            GoalCondition::WeightedSumAtLeast(weights, bound) => {
+                let mut total = 0.0;
+                for (field, weight) in weights {
+                    match numeric_field(state, field) {
+                        Some(v) => total += weight * v,
+                        None => return false,
+                    }
+                }
+                total >= *bound
+            }
+        }
+    }
+}
+
+fn numeric_field(state: &State, field: &str) -> Option<f64> {
+    state.get_field(field).and_then(Value::as_number)
+}
+
+// How many goal conditions a state satisfies out of all of them, see `Problem::goal_satisfaction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoalSatisfaction {
+    pub satisfied: usize,
+    pub total: usize,
+}
+
+impl GoalSatisfaction {
+    pub fn unsatisfied(&self) -> usize {
+        self.total - self.satisfied
+    }
+}
+
+// Structured goal description: a conjunction of conditions on state fields
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Goal {
+    pub conditions: Vec<GoalCondition>,
+}
+
+impl Goal {
+    pub fn new(conditions: Vec<GoalCondition>) -> Self {
+        Goal { conditions }
+    }
+
+    pub fn is_satisfied(&self, state: &State) -> bool {
+        self.conditions.iter().all(|condition| condition.is_satisfied(state))
+    }
+
+    // Number of conditions satisfied by `state`
+    pub fn satisfied_count(&self, state: &State) -> usize {
+        self.conditions.iter().filter(|condition| condition.is_satisfied(state)).count()
+    }
+
+    // Goal-count heuristic: number of conditions still unsatisfied (not admissible in general)
+    pub fn unsatisfied_count(&self, state: &State) -> f64 {
+        (self.conditions.len() - self.satisfied_count(state)) as f64
+    }
+
+    pub fn satisfaction(&self, state: &State) -> GoalSatisfaction {
+        GoalSatisfaction { satisfied: self.satisfied_count(state), total: self.conditions.len() }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goal_satisfaction_counts_conditions() {
+        let mut state = State::new();
+        state.insert_field("a".to_string(), Value::Int(3));
+        state.insert_field("b".to_string(), Value::Int(8));
+        state.insert_field("mode".to_string(), Value::Text("idle".to_string()));
+
+        let goal = Goal::new(vec![
+            GoalCondition::AtLeast("a".to_string(), 5.0),
+            GoalCondition::AtMost("b".to_string(), 10.0),
+            GoalCondition::Equals("mode".to_string(), Value::Text("idle".to_string())),
+            GoalCondition::WeightedSumAtLeast(BTreeMap::from([("a".to_string(), 1.0), ("b".to_string(), 0.5)]), 7.0),
+            GoalCondition::AtLeast("missing".to_string(), 0.0),
+        ]);
+
+        assert_eq!(goal.satisfied_count(&state), 3);
+        assert_eq!(goal.unsatisfied_count(&state), 2.0);
+        assert!(!goal.is_satisfied(&state));
+
+        state.insert_field("a".to_string(), Value::Int(5));
+        let reachable = Goal::new(goal.conditions[..4].to_vec());
+        assert!(reachable.is_satisfied(&state));
+    }
+}
@@ -1,5 +1,12 @@
 pub mod state;
 pub mod node;
+#[allow(clippy::module_inception)]
 pub mod search;
 pub mod action;
-pub(crate) mod search_tree;
\ No newline at end of file
+pub mod cost;
+pub mod validate_plan;
+pub mod goal;
+pub mod solver;
+pub mod plan_file;
+pub mod schema;
+pub mod search_tree;
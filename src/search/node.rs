@@ -1,27 +1,55 @@
 // use std::cell::RefCell;
 // use std::rc::{Rc, Weak};
-use crate::search::{state::{State, Value}, action::Action};
+use crate::search::{state::State, action::{Action, SearchAction}, cost::Cost};
+use serde::{Deserialize, Serialize};
 
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Node {
-    pub state: State,
+// Serialized as `{"state": ..., "parent": 0, "children": [2, 3], "action": ..., "cost": 1}`,
+// with `parent` and `action` null for the root; search dumps serialize the nodes of a tree in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Node<S = State, A: SearchAction = Action> {
+    pub state: S,
     pub parent: Option<usize>,
     pub children: Vec<usize>,
-    pub action: Option<Action>,
-    pub cost: i32,
+    pub action: Option<A>,
+    pub cost: A::Cost,
 }
 
-impl Node {
-    pub fn new_empty(state: State) -> Self {
+impl<S, A: SearchAction> Node<S, A> {
+    pub fn new_empty(state: S) -> Self {
         Node {
             state,
             parent: None,
             children: Vec::new(),
             action: None,
-            cost: 0,
+            cost: A::Cost::zero(),
 
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::state::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_node_json_round_trip() {
+        let mut state = State::new();
+        state.insert_field("health".to_string(), Value::Int(0));
+        let mut node: Node = Node::new_empty(state);
+        node.parent = Some(0);
+        node.children = vec![2, 3];
+        node.action = Some(Action::new("wait".to_string(), 1, HashMap::new()));
+        node.cost = 1;
+
+        let json = serde_json::to_string(&node).unwrap();
+        assert_eq!(
+            json,
+            r#"{"state":{"health":{"Int":0}},"parent":0,"children":[2,3],"action":{"name":"wait","cost":1,"parameters":{}},"cost":1}"#
+        );
+        assert_eq!(serde_json::from_str::<Node>(&json).unwrap(), node);
+    }
+}
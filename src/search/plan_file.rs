@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use crate::problems::problem::Problem;
+use crate::search::action::Action;
+use crate::search::state::Value;
+
+// Plans are written in four formats:
+// - plain text, readable with `read_plan`: one action per line, its name followed by `key=value`
+//   parameters sorted by key, e.g. `move-slow farm0=farm2 farm1=farm0`. Text that would read
+//   back as another value or be split apart is quoted, e.g. `note="1"` or `note="a b"`, with '"'
+//   and '\' escaped by a '\'. Values other than numbers, booleans and text are written in their
+//   debug form and read back as text. Blank lines and lines starting with ';' are ignored. Costs
+//   are not written; `resolve_plan` recovers them.
+// - JSON, readable with `read_plan_json`: the actions in their serde form with the step number
+//   and cumulative cost, optionally with the state reached after each step, which
+//   `read_plan_json_states` reads back
+// - IPC style, e.g. `(move-slow farm2 farm0)` or `(pick-up passenger1)`: the parameter values
+//   only, with '_' in action names written as '-', for external tools
+// - CSV: one row per step with the parameters as in the plain text format
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Int(v) => v.to_string(),
+        Value::Bool(v) => v.to_string(),
+        Value::Text(v) => v.clone(),
+        Value::Float(v) => format!("{:?}", v.0), // Keeps a fraction or exponent, e.g. `2.0`, to read back as a float
+        other => format!("{:?}", other),
+    }
+}
+
+// The value as written in the plain text format
+fn format_plain_value(value: &Value) -> String {
+    let text = match value {
+        Value::Int(_) | Value::Float(_) | Value::Bool(_) => return format_value(value),
+        Value::Text(v) => v.clone(),
+        other => format!("{:?}", other),
+    };
+    let reads_back = parse_value(&text) == Value::Text(text.clone()) && !text.contains(char::is_whitespace);
+    if reads_back && !text.is_empty() && !text.starts_with('"') {
+        text
+    } else {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn sorted_parameters(action: &Action) -> Vec<(&String, &Value)> {
+    let mut parameters: Vec<_> = action.parameters.iter().collect();
+    parameters.sort_by_key(|(key, _)| *key);
+    parameters
+}
+
+fn format_parameters(action: &Action) -> String {
+    let parameters = sorted_parameters(action).into_iter().map(|(key, value)| format!("{}={}", key, format_plain_value(value)));
+    parameters.collect::<Vec<_>>().join(" ")
+}
+
+pub fn format_action(action: &Action) -> String {
+    if action.parameters.is_empty() {
+        action.name.clone()
+    } else {
+        format!("{} {}", action.name, format_parameters(action))
+    }
+}
+
+pub fn write_plan(actions: &[Action]) -> String {
+    actions.iter().map(|action| format_action(action) + "\n").collect()
+}
+
+// Unquoted integers, floats and booleans are read back as such, anything else as text
+fn parse_value(value: &str) -> Value {
+    if let Ok(v) = value.parse::<i32>() {
+        Value::Int(v)
+    } else if let Some(v) = value.parse::<f64>().ok().filter(|v| v.is_finite()) {
+        Value::float(v)
+    } else if let Ok(v) = value.parse::<bool>() {
+        Value::Bool(v)
+    } else {
+        Value::Text(value.to_string())
+    }
+}
+
+// The text of a quoted value at the start of `quoted` and what follows its closing quote
+fn unquote(quoted: &str) -> Result<(String, &str), String> {
+    let mut text = String::new();
+    let mut chars = quoted.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => text.push(chars.next().ok_or("unterminated quoted value")?.1),
+            '"' => {
+                let rest = &quoted[index + 1..];
+                if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                    return Err(format!("expected a space after quoted value \"{}\"", text));
+                }
+                return Ok((text, rest));
+            }
+            _ => text.push(c),
+        }
+    }
+    Err("unterminated quoted value".to_string())
+}
+
+// Reads a line of the plain text format; the cost is set to 1
+pub fn parse_action(line: &str) -> Result<Action, String> {
+    let line = line.trim();
+    let (name, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    if name.is_empty() {
+        return Err("empty action".to_string());
+    }
+    let mut parameters = HashMap::new();
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        rest = &rest[start..];
+        let token = rest.split(char::is_whitespace).next().unwrap_or(rest);
+        let (key, value) = token
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", token))?;
+        if value.starts_with('"') {
+            let (text, remainder) = unquote(&rest[key.len() + 1..])?;
+            parameters.insert(key.to_string(), Value::Text(text));
+            rest = remainder;
+        } else {
+            parameters.insert(key.to_string(), parse_value(value));
+            rest = &rest[token.len()..];
+        }
+    }
+    Ok(Action::new(name.to_string(), 1, parameters))
+}
+
+pub fn read_plan(text: &str) -> Result<Vec<Action>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(';'))
+        .map(|(index, line)| parse_action(line).map_err(|message| format!("line {}: {}", index + 1, message)))
+        .collect()
+}
+
+// Replace every read action by the applicable action of the problem with the same name and
+// parameters, as found when replaying the plan. From the first action without a match on, the
+// read actions are kept as they are, so that `validate_plan` reports that step.
+pub fn resolve_plan<P: Problem<Action = Action>>(problem: &P, initial_state: &P::State, plan: &[Action]) -> Vec<Action> {
+    let mut state = initial_state.clone();
+    let mut resolved = Vec::with_capacity(plan.len());
+    for (step, action) in plan.iter().enumerate() {
+        let matching = problem
+            .get_possible_actions(&state)
+            .into_iter()
+            .find(|candidate| candidate.name == action.name && candidate.parameters == action.parameters);
+        match matching {
+            Some(matching) => {
+                state = problem.apply_action(&state, &matching);
+                resolved.push(matching);
+            }
+            None => {
+                resolved.extend_from_slice(&plan[step..]);
+                break;
+            }
+        }
+    }
+    resolved
+}
+
+// The state after each action of a plan of applicable actions, for `write_plan_json`
+pub fn plan_states<P: Problem>(problem: &P, initial_state: &P::State, plan: &[P::Action]) -> Vec<P::State> {
+    let mut state = initial_state.clone();
+    plan.iter()
+        .map(|action| {
+            state = problem.apply_action(&state, action);
+            state.clone()
+        })
+        .collect()
+}
+
+// Each step is the action in its serde form with the step number and cumulative cost added.
+// `states`, if given, holds the state after each action.
+pub fn plan_to_json<S: Serialize>(actions: &[Action], states: Option<&[S]>) -> Result<serde_json::Value, &'static str> {
+    if states.is_some_and(|states| states.len() != actions.len()) {
+        return Err("Expected one state per action");
+    }
+    let mut cumulative_cost: i32 = 0;
+    let mut steps = Vec::new();
+    for (index, action) in actions.iter().enumerate() {
+        cumulative_cost = cumulative_cost.checked_add(action.cost).ok_or("Plan cost overflow")?;
+        let mut step = json!(action);
+        step["step"] = json!(index + 1);
+        step["cumulative_cost"] = json!(cumulative_cost);
+        if let Some(states) = states {
+            step["state"] = json!(states[index]);
+        }
+        steps.push(step);
+    }
+    Ok(json!({ "length": actions.len(), "cost": cumulative_cost, "steps": steps }))
+}
+
+pub fn write_plan_json<S: Serialize>(actions: &[Action], states: Option<&[S]>) -> Result<String, &'static str> {
+    Ok(serde_json::to_string_pretty(&plan_to_json(actions, states)?).unwrap())
+}
+
+fn json_steps(text: &str) -> Result<Vec<serde_json::Value>, String> {
+    let mut plan: serde_json::Value = serde_json::from_str(text).map_err(|error| error.to_string())?;
+    match plan["steps"].take() {
+        serde_json::Value::Array(steps) => Ok(steps),
+        _ => Err("expected a 'steps' array".to_string()),
+    }
+}
+
+// Reads the actions of `write_plan_json` back, ignoring the cumulative costs and states
+pub fn read_plan_json(text: &str) -> Result<Vec<Action>, String> {
+    json_steps(text)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, step)| serde_json::from_value(step).map_err(|error| format!("step {}: {}", index + 1, error)))
+        .collect()
+}
+
+// Reads the states of a plan written by `write_plan_json` with states
+pub fn read_plan_json_states<S: DeserializeOwned>(text: &str) -> Result<Vec<S>, String> {
+    json_steps(text)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut step)| serde_json::from_value(step["state"].take()).map_err(|error| format!("step {}: {}", index + 1, error)))
+        .collect()
+}
+
+pub fn write_plan_ipc(actions: &[Action]) -> String {
+    actions
+        .iter()
+        .map(|action| {
+            let values = sorted_parameters(action).into_iter().map(|(_, value)| format!(" {}", format_value(value)));
+            format!("({}{})\n", action.name.replace('_', "-"), values.collect::<String>())
+        })
+        .collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn write_plan_csv(actions: &[Action]) -> Result<String, &'static str> {
+    let mut csv = String::from("step,name,parameters,cost,cumulative_cost\n");
+    let mut cumulative_cost: i32 = 0;
+    for (index, action) in actions.iter().enumerate() {
+        cumulative_cost = cumulative_cost.checked_add(action.cost).ok_or("Plan cost overflow")?;
+        let fields = [(index + 1).to_string(), csv_field(&action.name), csv_field(&format_parameters(action)), action.cost.to_string(), cumulative_cost.to_string()];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::farm_problem::farm_problem::FarmProblem;
+    use crate::problems::taxi_problem::taxi_problem::TaxiProblem;
+    use crate::search::state::{Position, State};
+    use std::collections::BTreeMap;
+    use crate::search::validate_plan::validate_plan;
+
+    #[test]
+    fn test_plan_round_trip_and_resolution() {
+        let problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let state = problem.initial_state();
+        let text = "; farm1 starts with a single unit\nmove-slow farm0=farm1 farm1=farm0\n\nmove-fast farm0=farm1 farm1=farm0\n";
+
+        let plan = read_plan(text).unwrap();
+        assert_eq!(write_plan(&plan), "move-slow farm0=farm1 farm1=farm0\nmove-fast farm0=farm1 farm1=farm0\n");
+
+        let resolved = resolve_plan(&problem, &state, &plan);
+        assert_eq!(validate_plan(&problem, &state, &resolved).unwrap().first_inapplicable_step, Some(1));
+        assert_eq!(read_plan("move-slow farm0").unwrap_err(), "line 1: expected key=value, got 'farm0'");
+
+        let rest = Action::new("rest".to_string(), 1, HashMap::from([("hours".to_string(), Value::float(2.0))]));
+        assert_eq!(write_plan(std::slice::from_ref(&rest)), "rest hours=2.0\n");
+        assert_eq!(read_plan(&write_plan(std::slice::from_ref(&rest))).unwrap(), vec![rest]);
+    }
+
+    #[test]
+    fn test_text_values_round_trip() {
+        let texts = ["1", "true", "2.5", "", "a b", "k=v", "say \"hi\"", "back\\slash", "\"", "farm0"];
+        let parameters = texts.iter().enumerate().map(|(index, text)| (format!("p{}", index), Value::from(*text)));
+        let mut parameters: HashMap<_, _> = parameters.collect();
+        parameters.insert("count".to_string(), Value::Int(1));
+        parameters.insert("flag".to_string(), Value::Bool(true));
+        let action = Action::new("note".to_string(), 1, parameters);
+
+        let text = write_plan(std::slice::from_ref(&action));
+        assert!(text.contains(" p0=\"1\" ") && text.contains(" p9=farm0") && text.contains(" count=1 "), "{}", text);
+        assert_eq!(read_plan(&text).unwrap(), vec![action]);
+        assert_eq!(read_plan("note a=\"b").unwrap_err(), "line 1: unterminated quoted value");
+        assert_eq!(read_plan("note a=\"b\"c").unwrap_err(), "line 1: expected a space after quoted value \"b\"");
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let state = problem.initial_state();
+        let plan = resolve_plan(&problem, &state, &read_plan("move-slow farm0=farm0 farm1=farm1\nmove-slow farm0=farm1 farm1=farm0\n").unwrap());
+
+        let states = plan_states(&problem, &state, &plan);
+        let json = write_plan_json(&plan, Some(&states)).unwrap();
+        assert_eq!(read_plan_json(&json).unwrap(), plan);
+        assert_eq!(read_plan_json_states::<State>(&json).unwrap(), states);
+        assert_eq!(plan_to_json(&plan, None::<&[()]>).unwrap()["steps"][1]["cumulative_cost"], plan[0].cost + plan[1].cost);
+
+        assert_eq!(write_plan_ipc(&plan), "(move-slow farm0 farm1)\n(move-slow farm1 farm0)\n");
+        assert_eq!(write_plan_ipc(&[TaxiProblem::pick_up("passenger1")]), "(pick-up passenger1)\n");
+        let csv = write_plan_csv(&plan).unwrap();
+        assert_eq!(csv.lines().nth(2).unwrap(), format!("2,move-slow,farm0=farm1 farm1=farm0,{},{}", plan[1].cost, plan[0].cost + plan[1].cost));
+        assert_eq!(read_plan_json("{\"steps\": [{\"name\": \"move-slow\"}]}").unwrap_err(), "step 1: missing field `cost`");
+
+        let positions = Value::Positions(BTreeMap::from([("taxi".to_string(), Position::new(1, 2))]));
+        let visit = vec![Action::new("visit".to_string(), 2, HashMap::from([("at".to_string(), positions)]))];
+        assert_eq!(read_plan_json(&write_plan_json::<()>(&visit, None).unwrap()).unwrap(), visit);
+    }
+
+    #[test]
+    fn test_cost_overflow_and_missing_states_are_errors() {
+        let plan = vec![Action::new("step".to_string(), i32::MAX / 2 + 1, HashMap::new()); 2];
+        assert_eq!(plan_to_json(&plan, None::<&[()]>), Err("Plan cost overflow"));
+        assert_eq!(write_plan_csv(&plan), Err("Plan cost overflow"));
+        assert_eq!(plan_to_json(&plan[..1], Some(&[0, 1])), Err("Expected one state per action"));
+        assert_eq!(plan_to_json(&plan, Some(&[0])), Err("Expected one state per action"));
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use crate::search::state::{State, Value, ValueKind};
+
+// Declared fields of a map-based `State`: their names, kinds and optional numeric ranges.
+// The declaration order gives the field indices of `CompactState`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StateSchema {
+    fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub kind: ValueKind,
+    // Inclusive bounds on the number of an Int or Float field, or on every element of an
+    // IntArray or MapToInt field
+    pub range: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub field: String,
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(field: &str, message: String) -> Self {
+        SchemaError { field: field.to_string(), message }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+// State whose values are stored by field index instead of by name, see `StateSchema::encode`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompactState(Vec<Value>);
+
+impl CompactState {
+    pub fn get(&self, index: usize) -> &Value {
+        &self.0[index]
+    }
+
+    pub fn set(&mut self, index: usize, value: Value) {
+        self.0[index] = value;
+    }
+}
+
+impl StateSchema {
+    pub fn new() -> Self {
+        StateSchema { fields: Vec::new() }
+    }
+
+    // Declaring a field again replaces the earlier declaration
+    pub fn field(self, name: &str, kind: ValueKind) -> Self {
+        self.declare(FieldSchema { name: name.to_string(), kind, range: None })
+    }
+
+    pub fn ranged_field(self, name: &str, kind: ValueKind, min: f64, max: f64) -> Self {
+        self.declare(FieldSchema { name: name.to_string(), kind, range: Some((min, max)) })
+    }
+
+    fn declare(mut self, field: FieldSchema) -> Self {
+        match self.index_of(&field.name) {
+            Some(index) => self.fields[index] = field,
+            None => self.fields.push(field),
+        }
+        self
+    }
+
+    pub fn fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    // All problems of `state`: undeclared fields (with the closest declared name, as they are
+    // usually typos), missing fields, values of the wrong kind and numbers out of range
+    pub fn validate(&self, state: &State) -> Result<(), Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        for (name, _) in state.fields() {
+            if self.index_of(name).is_none() {
+                let message = match self.closest_field(name) {
+                    Some(closest) => format!("unknown field '{}', did you mean '{}'?", name, closest),
+                    None => format!("unknown field '{}'", name),
+                };
+                errors.push(SchemaError::new(name, message));
+            }
+        }
+        for field in &self.fields {
+            if let Err(error) = field.check(state.get_field(&field.name)) {
+                errors.push(error);
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    // Read a state in the JSON representation of `search::state` and validate it
+    pub fn parse_state(&self, json: &str) -> Result<State, Vec<SchemaError>> {
+        let state: State = serde_json::from_str(json).map_err(|error| vec![SchemaError::new("", error.to_string())])?;
+        self.validate(&state)?;
+        Ok(state)
+    }
+
+    pub fn encode(&self, state: &State) -> Result<CompactState, Vec<SchemaError>> {
+        self.validate(state)?;
+        Ok(CompactState(self.fields.iter().map(|field| state.get_field(&field.name).unwrap().clone()).collect()))
+    }
+
+    pub fn decode(&self, compact: &CompactState) -> State {
+        let mut state = State::new();
+        for (field, value) in self.fields.iter().zip(&compact.0) {
+            state.set(&field.name, value.clone());
+        }
+        state
+    }
+
+    // Declared name within edit distance 2 of `name`, if any
+    fn closest_field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .map(|field| (edit_distance(name, &field.name), field.name.as_str()))
+            .filter(|(distance, _)| *distance <= 2)
+            .min()
+            .map(|(_, closest)| closest)
+    }
+}
+
+impl FieldSchema {
+    fn check(&self, value: Option<&Value>) -> Result<(), SchemaError> {
+        let error = |message: String| SchemaError::new(&self.name, message);
+        let value = value.ok_or_else(|| error(format!("missing field '{}', expected {}", self.name, self.kind.name())))?;
+        if value.value_kind() != self.kind {
+            return Err(error(format!("field '{}' is {}, expected {}", self.name, value.kind(), self.kind.name())));
+        }
+
+        let Some((min, max)) = self.range else {
+            return Ok(());
+        };
+        let numbers: Vec<f64> = match value {
+            Value::IntArray(values) => values.iter().map(|v| *v as f64).collect(),
+            Value::MapToInt(values) => values.values().map(|v| *v as f64).collect(),
+            other => other.as_number().into_iter().collect(),
+        };
+        match numbers.into_iter().find(|number| *number < min || *number > max) {
+            Some(number) => Err(error(format!("field '{}' has value {} outside the range {} to {}", self.name, number, min, max))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_schema() -> StateSchema {
+        StateSchema::new()
+            .ranged_field("farm0", ValueKind::Int, 0.0, 1000.0)
+            .ranged_field("farm1", ValueKind::Int, 0.0, 1000.0)
+            .field("goal_thresholds", ValueKind::MapToInt)
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let schema = create_schema();
+        let errors = schema
+            .parse_state(r#"{"farm0": {"Int": -1}, "farm_1": {"Int": 5}, "goal_thresholds": {"Int": 1}}"#)
+            .unwrap_err();
+        let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "unknown field 'farm_1', did you mean 'farm1'?",
+                "field 'farm0' has value -1 outside the range 0 to 1000",
+                "missing field 'farm1', expected Int",
+                "field 'goal_thresholds' is Int, expected MapToInt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_encoding_round_trip() {
+        let schema = create_schema();
+        let state = schema
+            .parse_state(r#"{"farm0": {"Int": 4}, "farm1": {"Int": 5}, "goal_thresholds": {"MapToInt": {"farm0": 1}}}"#)
+            .unwrap();
+
+        let mut compact = schema.encode(&state).unwrap();
+        assert_eq!(compact.get(schema.index_of("farm1").unwrap()), &Value::Int(5));
+        assert_eq!(schema.decode(&compact), state);
+
+        compact.set(0, Value::Int(7));
+        assert_eq!(schema.decode(&compact).get_int("farm0"), Ok(7));
+    }
+}
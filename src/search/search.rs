@@ -1,50 +1,283 @@
 use crate::algorithms::priority_queue::PriorityQueue;
 use crate::search::search_tree::SearchTree;
-use crate::search::action::Action;
-use crate::search::state::State;
-use std::collections::HashSet;
+use crate::search::cost::Cost;
+use crate::search::action::SearchAction;
+use crate::problems::problem::Problem;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::{BuildHasherDefault, DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+// Node counts gathered during a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchStatistics {
+    pub nodes_expanded: usize,
+    pub nodes_created: usize,
+    pub unique_nodes_created: usize,
+    pub nodes_pruned: usize, // Unique nodes discarded as dead ends or with an infinite heuristic
+}
+
+// Budgets after which a search gives up (unlimited by default) and evaluation options
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SearchOptions {
+    pub max_expansions: Option<usize>,
+    pub time_limit: Option<Duration>,
+    // Queue successors with their parent's heuristic value and evaluate a node only when it is
+    // expanded, saving heuristic calls for successors that are never expanded
+    pub lazy_heuristic: bool,
+}
+
+// Duplicate detection by state hash: stores the tree indices of the states seen instead of copies
+// of the states, and only compares states whose hashes are equal. `State` hashes in constant time
+// as it keeps its hash up to date.
+struct ClosedList {
+    first: HashMap<u64, usize, BuildHasherDefault<U64Hasher>>, // First node seen with each hash
+    collisions: HashMap<u64, Vec<usize>>,                      // Other nodes with an equal hash
+}
+
+// The keys of `ClosedList` are hashes already, so they are used as they are
+#[derive(Default)]
+struct U64Hasher(u64);
+
+impl Hasher for U64Hasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(*byte);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = value;
+    }
+}
+
+impl ClosedList {
+    fn new() -> Self {
+        ClosedList { first: HashMap::default(), collisions: HashMap::new() }
+    }
+
+    // Whether the state of node `index` had not been seen yet, recording it if so
+    fn insert<S: Eq + Hash + Debug, A: SearchAction>(&mut self, tree: &SearchTree<S, A>, index: usize) -> bool {
+        let state = &tree.get_node(index).unwrap().state;
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        match self.first.entry(hash) {
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+                true
+            }
+            Entry::Occupied(entry) => {
+                let same_state = |other: &usize| tree.get_node(*other).unwrap().state == *state;
+                if same_state(entry.get()) {
+                    return false;
+                }
+                let others = self.collisions.entry(hash).or_default();
+                if others.iter().any(same_state) {
+                    return false;
+                }
+                others.push(index);
+                true
+            }
+        }
+    }
+}
 
 // Generic search function that operates on a SearchTree and uses a priority queue for the search strategy
-pub fn generic_search<F, G, H, Q, I>(
-    tree: &mut SearchTree,
-    get_possible_actions: F,
-    apply_action: G,
+pub fn generic_search<S, A, F, H, D, Q, I>(
+    tree: &mut SearchTree<S, A>,
+    successors: F,
+    is_goal: H,
+    is_dead_end: D,
+    queue: Q,
+    heuristic: I,
+) -> Result<Vec<A>, &'static str>
+where
+    S: Clone + Eq + Hash + Debug,
+    A: SearchAction,
+    F: Fn(&S) -> Vec<(A, S)>,
+    H: Fn(&S) -> bool,
+    D: Fn(&S) -> bool,
+    Q: PriorityQueue<A::Cost>,
+    I: Fn(&S) -> f64,
+{
+    generic_search_with_statistics(tree, successors, is_goal, is_dead_end, queue, heuristic, SearchOptions::default()).0
+}
+
+// Search from `initial_state` of `problem` with the strategy given by `queue`, pruning the
+// problem's dead ends
+pub fn search_problem<P, Q>(problem: &P, initial_state: &P::State, queue: Q) -> Result<Vec<P::Action>, &'static str>
+where
+    P: Problem,
+    Q: PriorityQueue<P::Cost>,
+{
+    let mut tree = SearchTree::new(initial_state.clone());
+    generic_search(
+        &mut tree,
+        |state| problem.successors(state).collect(),
+        |state| problem.is_goal_state(state),
+        |state| problem.is_dead_end(state),
+        queue,
+        |state| problem.heuristic(state),
+    )
+}
+
+// Same as `generic_search` within `options`, also returning the node counts whether or not a
+// solution was found. Successors that are dead ends or whose heuristic is infinite are never queued.
+pub fn generic_search_with_statistics<S, A, F, H, D, Q, I>(
+    tree: &mut SearchTree<S, A>,
+    successors: F,
     is_goal: H,
+    is_dead_end: D,
     mut queue: Q,
     heuristic: I,
-) -> Result<Vec<Action>, &'static str>
+    options: SearchOptions,
+) -> (Result<Vec<A>, &'static str>, SearchStatistics)
 where
-    F: Fn(&State) -> Vec<Action>,
-    G: Fn(&State, &Action) -> State,
-    H: Fn(&State) -> bool,
-    Q: PriorityQueue,
-    I: Fn(&State) -> f64,
+    S: Clone + Eq + Hash + Debug,
+    A: SearchAction,
+    F: Fn(&S) -> Vec<(A, S)>,
+    H: Fn(&S) -> bool,
+    D: Fn(&S) -> bool,
+    Q: PriorityQueue<A::Cost>,
+    I: Fn(&S) -> f64,
 {
-    queue.insert(0, 0, f64::MAX);
-    let mut closed_list = HashSet::new();
-    let mut node_count = 0;
-    let mut unique_node_count = 0;
+    queue.insert(0, A::Cost::zero(), f64::MAX);
+    let mut closed_list = ClosedList::new();
+    let mut statistics = SearchStatistics::default();
+    let start_time = Instant::now();
 
     while let Some(current_index) = queue.pop() {
-        let successors = tree.expand_node(current_index, &get_possible_actions, &apply_action);
-        for &successor_index in &successors {
-            node_count += 1;
-            let successor_node = tree.get_node(successor_index).unwrap();
-            if !closed_list.insert(successor_node.state.clone()) {
+        if options.max_expansions.is_some_and(|max_expansions| statistics.nodes_expanded >= max_expansions) {
+            return (Err("Expansion limit reached"), statistics);
+        }
+        if options.time_limit.is_some_and(|time_limit| start_time.elapsed() >= time_limit) {
+            return (Err("Time limit reached"), statistics);
+        }
+        let mut parent_heuristic = 0.0;
+        if options.lazy_heuristic {
+            parent_heuristic = heuristic(&tree.get_node(current_index).unwrap().state);
+            if parent_heuristic == f64::INFINITY {
+                statistics.nodes_pruned += 1;
+                continue;
+            }
+        }
+        statistics.nodes_expanded += 1;
+        let successor_indices = match tree.expand_node_with_successors(current_index, &successors) {
+            Ok(successor_indices) => successor_indices,
+            Err(message) => return (Err(message), statistics),
+        };
+        for &successor_index in &successor_indices {
+            statistics.nodes_created += 1;
+            if !closed_list.insert(tree, successor_index) {
                 continue;
             }
-            unique_node_count += 1;
+            let successor_node = tree.get_node(successor_index).unwrap();
+            statistics.unique_nodes_created += 1;
             if is_goal(&successor_node.state) {
-                println!("Number of nodes created: {}", node_count);
-                println!("Number of unique nodes created: {}", unique_node_count);
-                return Ok(tree.trace_actions(successor_index));
+                return (Ok(tree.trace_actions(successor_index)), statistics);
+            }
+            if is_dead_end(&successor_node.state) {
+                statistics.nodes_pruned += 1;
+                continue;
+            }
+            if options.lazy_heuristic {
+                queue.insert(successor_index, successor_node.cost, parent_heuristic);
+                continue;
             }
             let heuristic_value = heuristic(&successor_node.state);
+            if heuristic_value == f64::INFINITY {
+                statistics.nodes_pruned += 1;
+                continue;
+            }
             queue.insert(successor_index, successor_node.cost, heuristic_value);
         }
     }
-    Err("No solution found")
+    (Err("No solution found"), statistics)
 }
 
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::astar::AStarQueue;
+    use crate::problems::taxi_problem::taxi_problem::small_taxi_problem;
+    use crate::search::action::Action;
+    use crate::search::solver::SolveResult;
+    use crate::search::state::Position;
 
+    #[test]
+    fn test_dead_ends_are_pruned() {
+        // The passenger waits behind a wall, out of the taxi's reach
+        let walls = [Position::new(1, 0), Position::new(1, 1)];
+        let problem = small_taxi_problem(3, 2, &walls, Position::new(0, 0), &[(Position::new(2, 0), Position::new(0, 1))]);
+        let state = problem.initial_state();
+        assert!(problem.is_dead_end(&state));
+
+        let mut tree = SearchTree::new(state.clone());
+        let (result, statistics) = generic_search_with_statistics(
+            &mut tree,
+            |state| problem.successors(state).collect(),
+            |state| problem.is_goal_state(state),
+            |state| problem.is_dead_end(state),
+            AStarQueue::new(),
+            |state| problem.heuristic(state),
+            SearchOptions::default(),
+        );
+
+        assert!(result.is_err());
+        // move_down and stay are generated from the root, then nothing is expanded
+        assert_eq!(statistics.nodes_created, 2);
+        assert_eq!(statistics.nodes_pruned, 2);
+    }
+
+    #[test]
+    fn test_cost_overflow_is_an_error() {
+        // Each step costs i32::MAX, so the second one overflows the path cost
+        let step = Action::new("step".to_string(), i32::MAX, HashMap::new());
+        let mut tree = SearchTree::new(0);
+        let result = generic_search(
+            &mut tree,
+            |state: &i32| vec![(step.clone(), state + 1)],
+            |state| *state == 3,
+            |_| false,
+            AStarQueue::new(),
+            |_| 0.0,
+        );
+        assert_eq!(result, Err("Path cost overflow"));
+
+        let solved = SolveResult { plan: Ok(vec![step.clone(), step]), statistics: SearchStatistics::default(), elapsed: Duration::ZERO };
+        assert_eq!(solved.cost(), Err("Plan cost overflow"));
+    }
+
+    // Every value hashes the same, so all states after the first go through the collision lists
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Colliding(i32);
+
+    impl Hash for Colliding {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            state.write_u64(0);
+        }
+    }
+
+    #[test]
+    fn test_closed_list_keeps_distinct_states_with_equal_hashes() {
+        let step = Action::new("step".to_string(), 1, HashMap::new());
+        let mut tree = SearchTree::new(Colliding(0));
+        let mut closed_list = ClosedList::new();
+        assert!(closed_list.insert(&tree, 0));
+
+        for value in [1, 2, 0, 1, 2] {
+            let index = tree.add_successor(0, step.clone(), Colliding(value)).unwrap();
+            assert_eq!(closed_list.insert(&tree, index), index <= 2, "state {}", value);
+        }
+        assert_eq!(closed_list.first.len(), 1);
+        assert_eq!(closed_list.collisions.values().map(Vec::len).sum::<usize>(), 2);
+    }
+}
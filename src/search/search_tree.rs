@@ -1,18 +1,19 @@
-use crate::search::{node::Node, state::State, action::Action, state::Value};
-use crate::algorithms::priority_queue::PriorityQueue;
-pub struct SearchTree {
-    pub nodes: Vec<Node>,    // A vector to store all nodes
+use crate::search::{node::Node, state::State, action::{Action, SearchAction}, cost::Cost};
+use std::fmt::Debug;
+
+pub struct SearchTree<S = State, A: SearchAction = Action> {
+    pub nodes: Vec<Node<S, A>>,    // A vector to store all nodes
 }
 
-impl SearchTree {
+impl<S: Debug, A: SearchAction> SearchTree<S, A> {
     // Create a new empty tree with an initial node
-    pub fn new(initial_state: State) -> Self {
+    pub fn new(initial_state: S) -> Self {
         let root = Node {
             state: initial_state,
             parent: None,
             children: Vec::new(),
             action: None,
-            cost: 0,
+            cost: A::Cost::zero(),
         };
 
         SearchTree {
@@ -21,13 +22,18 @@ impl SearchTree {
     }
 
     // Add a new node to the tree given a parent index and an action
-    pub fn add_node<F>(&mut self, parent_index: usize, action: Action, apply_action: F) -> usize
+    pub fn add_node<F>(&mut self, parent_index: usize, action: A, apply_action: F) -> Result<usize, &'static str>
     where
-        F: Fn(&State, &Action) -> State,
+        F: Fn(&S, &A) -> S,
     {
-        let parent_node = &self.nodes[parent_index];
-        let new_state = apply_action(&parent_node.state, &action);
-        let new_cost = parent_node.cost + action.cost;
+        let new_state = apply_action(&self.nodes[parent_index].state, &action);
+        self.add_successor(parent_index, action, new_state)
+    }
+
+    // Add a new node for a successor state that was already computed.
+    // Fails if the path cost overflows the cost type
+    pub fn add_successor(&mut self, parent_index: usize, action: A, new_state: S) -> Result<usize, &'static str> {
+        let new_cost = self.nodes[parent_index].cost.checked_add(action.cost()).ok_or("Path cost overflow")?;
 
         let new_node = Node {
             state: new_state,
@@ -43,16 +49,16 @@ impl SearchTree {
         // Update the parent's children list
         self.nodes[parent_index].children.push(new_node_index);
 
-        new_node_index
+        Ok(new_node_index)
     }
 
     // Get the node by its index
-    pub fn get_node(&self, index: usize) -> Option<&Node> {
+    pub fn get_node(&self, index: usize) -> Option<&Node<S, A>> {
         self.nodes.get(index)
     }
 
 
-    pub fn trace_actions(&self, node_index: usize) -> Vec<Action> {
+    pub fn trace_actions(&self, node_index: usize) -> Vec<A> {
         let mut actions = Vec::new();
         let mut current_index = Some(node_index);
         while let Some(index) = current_index {
@@ -70,31 +76,46 @@ impl SearchTree {
     }
 
 
-    pub fn expand_node<F, G>(&mut self, node_index: usize, get_possible_actions: F, apply_action: G) -> Vec<usize>
+    pub fn expand_node<F, G>(&mut self, node_index: usize, get_possible_actions: F, apply_action: G) -> Result<Vec<usize>, &'static str>
+    where
+        F: Fn(&S) -> Vec<A>,
+        G: Fn(&S, &A) -> S,
+    {
+        self.expand_node_with_successors(node_index, |state| {
+            get_possible_actions(state)
+                .into_iter()
+                .map(|action| {
+                    let new_state = apply_action(state, &action);
+                    (action, new_state)
+                })
+                .collect()
+        })
+    }
+
+    // Expand a node from (action, successor state) pairs generated in a single pass
+    pub fn expand_node_with_successors<F>(&mut self, node_index: usize, successors: F) -> Result<Vec<usize>, &'static str>
     where
-        F: Fn(&State) -> Vec<Action>,
-        G: Fn(&State, &Action) -> State,
+        F: FnOnce(&S) -> Vec<(A, S)>,
     {
-        let mut successors = Vec::new();
+        let mut successor_indices = Vec::new();
         if let Some(node) = self.get_node(node_index) {
-            let actions = get_possible_actions(&node.state);
-            for action in actions {
-                let new_node_index = self.add_node(node_index, action, &apply_action);
-                successors.push(new_node_index);
+            for (action, new_state) in successors(&node.state) {
+                let new_node_index = self.add_successor(node_index, action, new_state)?;
+                successor_indices.push(new_node_index);
             }
         }
-        successors
+        Ok(successor_indices)
     }
 
     pub fn print_tree(&self, node_index: usize, indent: usize) {
         if let Some(node) = self.get_node(node_index) {
             // Print the current node details with indentation to show hierarchy
             println!(
-                "{:indent$}Node Index: {}, Cost: {}, Action: {:?}, State: {:?}",
+                "{:indent$}Node Index: {}, Cost: {:?}, Action: {:?}, State: {:?}",
                 "",
                 node_index,
                 node.cost,
-                node.action.as_ref().map(|a| &a.name),
+                node.action.as_ref().map(|a| a.name()),
                 node.state,
                 indent = indent
             );
@@ -113,6 +134,7 @@ impl SearchTree {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::state::Value;
     use std::collections::HashMap;
 
     // Helper function to create an action with specified parameters
@@ -143,10 +165,10 @@ mod tests {
 
         // Add nodes to the tree
         let action1 = create_action("increase_health", 5, 10);
-        let first_node_index = tree.add_node(0, action1.clone(), &apply_action);
+        let first_node_index = tree.add_node(0, action1.clone(), apply_action).unwrap();
 
         let action2 = create_action("increase_health", 3, 5);
-        let second_node_index = tree.add_node(first_node_index, action2.clone(), &apply_action);
+        let second_node_index = tree.add_node(first_node_index, action2.clone(), apply_action).unwrap();
 
         // Trace actions back from the second node to the root
         let traced_actions = tree.trace_actions(second_node_index);
@@ -161,7 +183,7 @@ mod tests {
         let mut tree = SearchTree::new(initial_state);
 
         // Define get_possible_actions logic
-        let get_possible_actions = |state: &State| {
+        let get_possible_actions = |_state: &State| {
             let mut actions = Vec::new();
             for amount in 1..=3 {
                 let mut parameters = HashMap::new();
@@ -183,7 +205,7 @@ mod tests {
         };
 
         // Expand the root node
-        let successors = tree.expand_node(0, get_possible_actions, apply_action);
+        let successors = tree.expand_node(0, get_possible_actions, apply_action).unwrap();
 
         // Check that the correct number of successors were created
         assert_eq!(successors.len(), 3);
@@ -194,4 +216,48 @@ mod tests {
         assert_eq!(tree.get_node(successors[2]).unwrap().state.get_field("health"), Some(&Value::Int(53)));
         tree.print_tree(0, 2);
     }
+
+    // An action type with fractional costs, moving along a number line
+    #[derive(Debug, Clone, PartialEq)]
+    struct Step {
+        delta: i32,
+        cost: f64,
+    }
+
+    impl SearchAction for Step {
+        type Cost = f64;
+
+        fn cost(&self) -> f64 {
+            self.cost
+        }
+
+        fn name(&self) -> String {
+            format!("step{:+}", self.delta)
+        }
+    }
+
+    #[test]
+    fn test_custom_action_and_cost_types() {
+        let mut tree: SearchTree<i32, Step> = SearchTree::new(0);
+        let apply_step = |position: &i32, step: &Step| position + step.delta;
+
+        let first = Step { delta: 2, cost: 0.25 };
+        let second = Step { delta: -1, cost: 0.5 };
+        let first_index = tree.add_node(0, first.clone(), apply_step).unwrap();
+        let second_index = tree.add_node(first_index, second.clone(), apply_step).unwrap();
+
+        let node = tree.get_node(second_index).unwrap();
+        assert_eq!(node.state, 1);
+        assert_eq!(node.cost, 0.75);
+        assert_eq!(tree.trace_actions(second_index), vec![first, second]);
+    }
+
+    #[test]
+    fn test_path_cost_overflow() {
+        let mut tree = SearchTree::new(State::new());
+        let apply_action = |state: &State, _action: &Action| state.clone();
+        let first_index = tree.add_node(0, create_action("expensive", i32::MAX, 0), apply_action).unwrap();
+        assert_eq!(tree.add_node(first_index, create_action("expensive", 1, 0), apply_action), Err("Path cost overflow"));
+        assert_eq!(tree.nodes.len(), 2);
+    }
 }
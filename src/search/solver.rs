@@ -0,0 +1,215 @@
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::algorithms::priority_queue::PriorityQueue;
+use crate::algorithms::{astar::AStarQueue, bfs::BfsQueue, dfs::DfsQueue, gbfs::GBFSQueue};
+use crate::algorithms::weighted_astar::{TieBreaking, WeightedAStarQueue};
+use crate::problems::problem::Problem;
+use crate::search::action::SearchAction;
+use crate::search::cost::Cost;
+use crate::search::search::{generic_search_with_statistics, SearchOptions, SearchStatistics};
+use crate::search::search_tree::SearchTree;
+
+// Search strategies available to `Solver`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Bfs,
+    Dfs,
+    Gbfs,
+    AStar,
+    WeightedAStar(f64), // Weight applied to the heuristic
+}
+
+impl Algorithm {
+    // Tie breaking only applies to the A* variants
+    pub fn create_queue<C: Cost>(&self, tie_breaking: TieBreaking) -> Box<dyn PriorityQueue<C>> {
+        match (self, tie_breaking) {
+            (Algorithm::Bfs, _) => Box::new(BfsQueue::new()),
+            (Algorithm::Dfs, _) => Box::new(DfsQueue::new()),
+            (Algorithm::Gbfs, _) => Box::new(GBFSQueue::new()),
+            (Algorithm::AStar, TieBreaking::Fifo) => Box::new(AStarQueue::new()),
+            (Algorithm::AStar, _) => Box::new(WeightedAStarQueue::new(1.0).with_tie_breaking(tie_breaking)),
+            (Algorithm::WeightedAStar(weight), _) => Box::new(WeightedAStarQueue::new(*weight).with_tie_breaking(tie_breaking)),
+        }
+    }
+}
+
+// Heuristic chosen at runtime, replacing `Problem::heuristic`
+pub type Heuristic<'a, P> = Rc<dyn Fn(&<P as Problem>::State) -> f64 + 'a>;
+
+// Everything a configuration string selects (see `config::registry`)
+pub struct SearchConfiguration<'a, P: Problem> {
+    pub algorithm: Algorithm,
+    pub tie_breaking: TieBreaking,
+    pub lazy_heuristic: bool,
+    pub heuristic: Heuristic<'a, P>,
+}
+
+// Outcome of `Solver::solve`; the statistics are kept when the search fails
+#[derive(Debug, Clone)]
+pub struct SolveResult<A: SearchAction> {
+    pub plan: Result<Vec<A>, &'static str>,
+    pub statistics: SearchStatistics,
+    pub elapsed: Duration,
+}
+
+impl<A: SearchAction> SolveResult<A> {
+    pub fn is_solved(&self) -> bool {
+        self.plan.is_ok()
+    }
+
+    // Total cost of the plan; the search error without a plan
+    pub fn cost(&self) -> Result<A::Cost, &'static str> {
+        let actions = self.plan.as_ref().map_err(|message| *message)?;
+        actions
+            .iter()
+            .try_fold(A::Cost::zero(), |total, action| total.checked_add(action.cost()))
+            .ok_or("Plan cost overflow")
+    }
+}
+
+// Builder over `generic_search`: pick the algorithm, budgets and options, then call `solve`
+//
+//     let result = Solver::new(&problem).algorithm(Algorithm::AStar).max_expansions(10_000).solve();
+pub struct Solver<'a, P: Problem> {
+    problem: &'a P,
+    algorithm: Algorithm,
+    tie_breaking: TieBreaking,
+    heuristic: Option<Heuristic<'a, P>>,
+    initial_state: Option<P::State>,
+    options: SearchOptions,
+    prune_dead_ends: bool,
+}
+
+impl<'a, P: Problem> Solver<'a, P> {
+    // A* from the problem's initial state, without budgets, pruning dead ends
+    pub fn new(problem: &'a P) -> Self {
+        Solver {
+            problem,
+            algorithm: Algorithm::AStar,
+            tie_breaking: TieBreaking::Fifo,
+            heuristic: None,
+            initial_state: None,
+            options: SearchOptions::default(),
+            prune_dead_ends: true,
+        }
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn tie_breaking(mut self, tie_breaking: TieBreaking) -> Self {
+        self.tie_breaking = tie_breaking;
+        self
+    }
+
+    // Use `heuristic` instead of `Problem::heuristic`
+    pub fn heuristic(mut self, heuristic: impl Fn(&P::State) -> f64 + 'a) -> Self {
+        self.heuristic = Some(Rc::new(heuristic));
+        self
+    }
+
+    pub fn lazy_heuristic(mut self, lazy_heuristic: bool) -> Self {
+        self.options.lazy_heuristic = lazy_heuristic;
+        self
+    }
+
+    // Apply a configuration built from a configuration string
+    pub fn configure(mut self, configuration: SearchConfiguration<'a, P>) -> Self {
+        self.algorithm = configuration.algorithm;
+        self.tie_breaking = configuration.tie_breaking;
+        self.options.lazy_heuristic = configuration.lazy_heuristic;
+        self.heuristic = Some(configuration.heuristic);
+        self
+    }
+
+    // Search from `state` instead of `Problem::initial_state`
+    pub fn initial_state(mut self, state: P::State) -> Self {
+        self.initial_state = Some(state);
+        self
+    }
+
+    pub fn max_expansions(mut self, max_expansions: usize) -> Self {
+        self.options.max_expansions = Some(max_expansions);
+        self
+    }
+
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.options.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn prune_dead_ends(mut self, prune_dead_ends: bool) -> Self {
+        self.prune_dead_ends = prune_dead_ends;
+        self
+    }
+
+    pub fn solve(&self) -> SolveResult<P::Action> {
+        let start_time = Instant::now();
+        let initial_state = self.initial_state.clone().unwrap_or_else(|| self.problem.initial_state());
+        let mut tree: SearchTree<P::State, P::Action> = SearchTree::new(initial_state);
+
+        let (plan, statistics) = generic_search_with_statistics(
+            &mut tree,
+            |state| self.problem.successors(state).collect(),
+            |state| self.problem.is_goal_state(state),
+            |state| self.prune_dead_ends && self.problem.is_dead_end(state),
+            self.algorithm.create_queue::<P::Cost>(self.tie_breaking),
+            |state| match &self.heuristic {
+                Some(heuristic) => heuristic(state),
+                None => self.problem.heuristic(state),
+            },
+            self.options,
+        );
+
+        SolveResult {
+            plan,
+            statistics,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::taxi_problem::taxi_problem::{small_taxi_problem, TaxiProblem};
+    use crate::search::state::Position;
+    use crate::search::validate_plan::validate_plan;
+
+    fn create_problem() -> TaxiProblem {
+        let walls = [Position::new(1, 0), Position::new(1, 1)];
+        small_taxi_problem(4, 4, &walls, Position::new(0, 0), &[(Position::new(3, 0), Position::new(0, 3))])
+    }
+
+    #[test]
+    fn test_algorithms_are_interchangeable() {
+        let problem = create_problem();
+        let algorithms = [Algorithm::Bfs, Algorithm::Dfs, Algorithm::Gbfs, Algorithm::AStar, Algorithm::WeightedAStar(2.0)];
+
+        for algorithm in algorithms {
+            let result = Solver::new(&problem).algorithm(algorithm).solve();
+            let plan = result.plan.as_ref().unwrap();
+            let validation = validate_plan(&problem, &problem.initial_state(), plan).unwrap();
+            assert!(validation.is_valid(), "{:?}", algorithm);
+            assert_eq!(result.cost(), Ok(validation.cost));
+            if matches!(algorithm, Algorithm::Bfs | Algorithm::AStar) {
+                // 7 moves around the wall to the passenger, 6 to the goal, pick up and disembark
+                assert_eq!(result.cost(), Ok(15));
+            }
+        }
+    }
+
+    #[test]
+    fn test_expansion_budget() {
+        let problem = create_problem();
+
+        let result = Solver::new(&problem).algorithm(Algorithm::Bfs).max_expansions(3).solve();
+
+        assert_eq!(result.plan, Err("Expansion limit reached"));
+        assert_eq!(result.statistics.nodes_expanded, 3);
+        assert_eq!(result.cost(), Err("Expansion limit reached"));
+    }
+}
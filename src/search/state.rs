@@ -0,0 +1,582 @@
+use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::search::cost::OrderedFloat;
+
+// JSON representation, stable across versions:
+// - `Position` is an object `{"x": 1, "y": 2}`
+// - `Value` is an object with the variant name as its single key, e.g. `{"Int": 3}`,
+//   `{"Text": "farm0"}`, `{"Float": 1.5}`, `{"IntArray": [1, 2]}`, `{"Set": [{"Int": 1}]}`
+//   (in order), `{"Map": {"farm0": {"Float": 2.7}}}`, `{"List": [...]}`, `{"Tuple": [...]}` or
+//   `{"Positions": {"taxi": {"x": 0, "y": 1}}}`
+// - `State` is an object mapping its field names to their values, in key order
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Serialize, Deserialize)]
+pub struct Position{
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Position { x, y }
+    }
+}
+
+// Values are ordered (floats by `f64::total_cmp`) so that they can be elements of a `Set`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Value {
+    Int(i32),
+    Text(String),
+    Bool(bool),
+    IntArray(Vec<i32>),
+    Positions(BTreeMap<String, Position>),
+    MapToVecString(BTreeMap<String, Vec<String>>),
+    MapToString(BTreeMap<String, String>),
+    MapToInt(BTreeMap<String, i32>),
+    Float(OrderedFloat),
+    Set(BTreeSet<Value>),
+    Map(BTreeMap<String, Value>),
+    List(Vec<Value>),
+    Tuple(Vec<Value>), // Fixed-size group of values of possibly different kinds, e.g. (farm, amount)
+}
+
+// The variants of `Value`, e.g. for declaring field types in a `StateSchema`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Int,
+    Text,
+    Bool,
+    IntArray,
+    Positions,
+    MapToVecString,
+    MapToString,
+    MapToInt,
+    Float,
+    Set,
+    Map,
+    List,
+    Tuple,
+}
+
+impl ValueKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueKind::Int => "Int",
+            ValueKind::Text => "Text",
+            ValueKind::Bool => "Bool",
+            ValueKind::IntArray => "IntArray",
+            ValueKind::Positions => "Positions",
+            ValueKind::MapToVecString => "MapToVecString",
+            ValueKind::MapToString => "MapToString",
+            ValueKind::MapToInt => "MapToInt",
+            ValueKind::Float => "Float",
+            ValueKind::Set => "Set",
+            ValueKind::Map => "Map",
+            ValueKind::List => "List",
+            ValueKind::Tuple => "Tuple",
+        }
+    }
+}
+
+impl Value {
+    pub fn float(value: f64) -> Self {
+        Value::Float(OrderedFloat(value))
+    }
+
+    pub fn value_kind(&self) -> ValueKind {
+        match self {
+            Value::Int(_) => ValueKind::Int,
+            Value::Text(_) => ValueKind::Text,
+            Value::Bool(_) => ValueKind::Bool,
+            Value::IntArray(_) => ValueKind::IntArray,
+            Value::Positions(_) => ValueKind::Positions,
+            Value::MapToVecString(_) => ValueKind::MapToVecString,
+            Value::MapToString(_) => ValueKind::MapToString,
+            Value::MapToInt(_) => ValueKind::MapToInt,
+            Value::Float(_) => ValueKind::Float,
+            Value::Set(_) => ValueKind::Set,
+            Value::Map(_) => ValueKind::Map,
+            Value::List(_) => ValueKind::List,
+            Value::Tuple(_) => ValueKind::Tuple,
+        }
+    }
+
+    // Name of the variant, as used in the JSON representation and in `StateError`
+    pub fn kind(&self) -> &'static str {
+        self.value_kind().name()
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Value::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Float(v) => Some(v.0),
+            _ => None,
+        }
+    }
+
+    // Integers and floats alike
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Int(v) => Some(*v as f64),
+            Value::Float(v) => Some(v.0),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_set(&self) -> Option<&BTreeSet<Value>> {
+        match self {
+            Value::Set(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_tuple(&self) -> Option<&[Value]> {
+        match self {
+            Value::Tuple(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+
+
+// Fields sorted by name. Names are interned and values shared between the clones of a state, so
+// cloning a state copies one vector of pointers and changing a field allocates only that field.
+// The hash of a state is the XOR of the hashes of its fields (Zobrist-style), kept up to date as
+// fields change, so hashing a state costs nothing and unequal states rarely need comparing.
+#[derive(Clone, Default)]
+pub struct State {
+    fields: Vec<Field>,
+    hash: u64,
+}
+
+#[derive(Clone)]
+struct Field {
+    name: FieldName,
+    value: Arc<Value>,
+    hash: u64, // Of the name and value
+}
+
+impl Field {
+    fn new(name: FieldName, value: Arc<Value>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        value.hash(&mut hasher);
+        Field { name, value, hash: hasher.finish() }
+    }
+}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.name == other.name && self.value == other.value
+    }
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.fields == other.fields
+    }
+}
+
+impl Eq for State {}
+
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+// Interned field name: equal names share their allocation, on every thread, and compare by pointer
+#[derive(Clone, Eq)]
+struct FieldName(Arc<str>);
+
+static FIELD_NAMES: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+
+impl FieldName {
+    fn intern(name: &str) -> Self {
+        let mut names = FIELD_NAMES.get_or_init(Mutex::default).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match names.get(name) {
+            Some(interned) => FieldName(interned.clone()),
+            None => {
+                let interned: Arc<str> = Arc::from(name);
+                names.insert(interned.clone());
+                FieldName(interned)
+            }
+        }
+    }
+}
+
+impl PartialEq for FieldName {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl Hash for FieldName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.fields()).finish()
+    }
+}
+
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.fields())
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = BTreeMap::<String, Value>::deserialize(deserializer)?;
+        let mut state = State::new();
+        for (name, value) in fields {
+            state.insert_field(name, value);
+        }
+        Ok(state)
+    }
+}
+
+// A field that is missing or holds a value of another type than the one asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateError {
+    pub field: String,
+    pub expected: &'static str,
+    pub found: Option<&'static str>, // Kind of the value found, None if the field is missing
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.found {
+            None => write!(f, "missing field '{}', expected {}", self.field, self.expected),
+            Some(found) => write!(f, "field '{}' is {}, expected {}", self.field, found, self.expected),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl State {
+    pub fn new() -> Self {
+        State {
+            fields: Vec::new(),
+            hash: 0,
+        }
+    }
+
+    fn position(&self, key: &str) -> Result<usize, usize> {
+        self.fields.binary_search_by(|field| (*field.name.0).cmp(key))
+    }
+
+    pub fn insert_field(&mut self, key: String, value: Value) {
+        self.set(&key, value);
+    }
+
+    pub fn get_field(&self, key: &str) -> Option<&Value> {
+        self.position(key).ok().map(|index| &*self.fields[index].value)
+    }
+
+    // Fields in key order
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields.iter().map(|field| (&*field.name.0, &*field.value))
+    }
+
+    pub fn set(&mut self, field: &str, value: impl Into<Value>) {
+        let value = Arc::new(value.into());
+        match self.position(field) {
+            Ok(index) => self.replace(index, value),
+            Err(index) => {
+                let field = Field::new(FieldName::intern(field), value);
+                self.hash ^= field.hash;
+                self.fields.insert(index, field);
+            }
+        }
+    }
+
+    fn replace(&mut self, index: usize, value: Arc<Value>) {
+        let field = Field::new(self.fields[index].name.clone(), value);
+        self.hash ^= self.fields[index].hash ^ field.hash;
+        self.fields[index] = field;
+    }
+
+    fn get_as<'s, T>(&'s self, field: &str, expected: &'static str, convert: impl FnOnce(&'s Value) -> Option<T>) -> Result<T, StateError> {
+        let value = self.get_field(field);
+        value.and_then(convert).ok_or_else(|| StateError {
+            field: field.to_string(),
+            expected,
+            found: value.map(Value::kind),
+        })
+    }
+
+    pub fn get_int(&self, field: &str) -> Result<i32, StateError> {
+        self.get_as(field, "Int", Value::as_int)
+    }
+
+    pub fn get_float(&self, field: &str) -> Result<f64, StateError> {
+        self.get_as(field, "Float", Value::as_float)
+    }
+
+    // Int or Float
+    pub fn get_number(&self, field: &str) -> Result<f64, StateError> {
+        self.get_as(field, "a number", Value::as_number)
+    }
+
+    pub fn get_bool(&self, field: &str) -> Result<bool, StateError> {
+        self.get_as(field, "Bool", Value::as_bool)
+    }
+
+    pub fn get_text(&self, field: &str) -> Result<&str, StateError> {
+        self.get_as(field, "Text", Value::as_text)
+    }
+
+    pub fn get_positions(&self, field: &str) -> Result<&BTreeMap<String, Position>, StateError> {
+        self.get_as(field, "Positions", |value| match value {
+            Value::Positions(positions) => Some(positions),
+            _ => None,
+        })
+    }
+
+    pub fn get_map_int(&self, field: &str) -> Result<&BTreeMap<String, i32>, StateError> {
+        self.get_as(field, "MapToInt", |value| match value {
+            Value::MapToInt(map) => Some(map),
+            _ => None,
+        })
+    }
+
+    pub fn get_map_vec_string(&self, field: &str) -> Result<&BTreeMap<String, Vec<String>>, StateError> {
+        self.get_as(field, "MapToVecString", |value| match value {
+            Value::MapToVecString(map) => Some(map),
+            _ => None,
+        })
+    }
+
+    pub fn get_map(&self, field: &str) -> Result<&BTreeMap<String, Value>, StateError> {
+        self.get_as(field, "Map", Value::as_map)
+    }
+
+    pub fn get_list(&self, field: &str) -> Result<&[Value], StateError> {
+        self.get_as(field, "List", Value::as_list)
+    }
+
+    pub fn get_set(&self, field: &str) -> Result<&BTreeSet<Value>, StateError> {
+        self.get_as(field, "Set", Value::as_set)
+    }
+
+    // Modify an existing field in place
+    pub fn update_with<T>(&mut self, field: &str, update: impl FnOnce(&mut Value) -> T) -> Result<T, StateError> {
+        match self.position(field) {
+            // Copies the value first if other states share it
+            Ok(index) => {
+                let mut value = self.fields[index].value.clone();
+                let result = update(Arc::make_mut(&mut value));
+                self.replace(index, value);
+                Ok(result)
+            }
+            Err(_) => Err(StateError { field: field.to_string(), expected: "a value", found: None }),
+        }
+    }
+
+    pub fn update_int(&mut self, field: &str, update: impl FnOnce(i32) -> i32) -> Result<(), StateError> {
+        let value = self.get_int(field)?;
+        self.set(field, update(value));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_representation_round_trips() {
+        let mut state = State::new();
+        state.insert_field("amount".to_string(), Value::Int(3));
+        state.insert_field("at".to_string(), Value::Positions(BTreeMap::from([("taxi".to_string(), Position::new(0, 1))])));
+        state.insert_field("done".to_string(), Value::Bool(false));
+        state.insert_field("farms".to_string(), Value::MapToVecString(BTreeMap::from([("farm0".to_string(), vec!["farm1".to_string()])])));
+        state.insert_field("levels".to_string(), Value::IntArray(vec![1, 2]));
+        state.insert_field("name".to_string(), Value::Text("farm0".to_string()));
+        state.insert_field("owners".to_string(), Value::MapToString(BTreeMap::from([("farm0".to_string(), "ann".to_string())])));
+        state.insert_field("resources".to_string(), Value::MapToInt(BTreeMap::from([("farm0".to_string(), 4)])));
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"amount":{"Int":3},"at":{"Positions":{"taxi":{"x":0,"y":1}}},"done":{"Bool":false},"#,
+                r#""farms":{"MapToVecString":{"farm0":["farm1"]}},"levels":{"IntArray":[1,2]},"name":{"Text":"farm0"},"#,
+                r#""owners":{"MapToString":{"farm0":"ann"}},"resources":{"MapToInt":{"farm0":4}}}"#
+            )
+        );
+        assert_eq!(serde_json::from_str::<State>(&json).unwrap(), state);
+        assert!(serde_json::from_str::<State>(r#"{"amount":{"Double":3.5}}"#).is_err());
+    }
+
+    #[test]
+    fn test_nested_values() {
+        let route = Value::List(vec![
+            Value::Tuple(vec!["farm0".into(), 2.5.into()]),
+            Value::Tuple(vec!["farm1".into(), 4.into()]),
+        ]);
+        let visited = Value::Set(BTreeSet::from([3.into(), 1.into(), 2.0.into(), 1.into()]));
+        let value = Value::Map(BTreeMap::from([("route".to_string(), route), ("visited".to_string(), visited)]));
+
+        let map = value.as_map().unwrap();
+        let first = map["route"].as_list().unwrap()[0].as_tuple().unwrap();
+        assert_eq!((first[0].as_text(), first[1].as_float()), (Some("farm0"), Some(2.5)));
+        assert_eq!(map["route"].as_list().unwrap()[1].as_tuple().unwrap()[1].as_number(), Some(4.0));
+        assert_eq!(map["visited"].as_set().unwrap().len(), 3);
+        assert_eq!(Value::Int(1).as_float(), None);
+
+        // Floats hash and compare by value, so equal nested values are equal states
+        let json = serde_json::to_string(&value).unwrap();
+        assert!(json.contains(r#""visited":{"Set":[{"Int":1},{"Int":3},{"Float":2.0}]}"#));
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut state = State::new();
+        state.set("cost", 3);
+        state.set("name", "farm0");
+
+        state.update_int("cost", |cost| cost + 1).unwrap();
+        assert_eq!(state.get_int("cost"), Ok(4));
+        assert_eq!(state.get_text("name"), Ok("farm0"));
+        assert_eq!(state.get_int("name").unwrap_err().to_string(), "field 'name' is Text, expected Int");
+        assert_eq!(state.get_map_int("goals").unwrap_err().to_string(), "missing field 'goals', expected MapToInt");
+        assert!(state.update_with("goals", |_| ()).is_err());
+    }
+
+    #[test]
+    fn test_clones_share_unchanged_fields() {
+        let mut state = State::new();
+        state.set("taxi", Value::Positions(BTreeMap::from([("taxi".to_string(), Position::new(0, 0))])));
+        state.set("passengers", Value::IntArray(vec![1, 2, 3]));
+
+        let mut successor = state.clone();
+        successor.set("taxi", Value::Positions(BTreeMap::from([("taxi".to_string(), Position::new(1, 0))])));
+        // Fields are sorted by name: passengers, then taxi
+        assert!(Arc::ptr_eq(&state.fields[0].value, &successor.fields[0].value));
+        assert!(!Arc::ptr_eq(&state.fields[1].value, &successor.fields[1].value));
+        assert!(Arc::ptr_eq(&state.fields[1].name.0, &successor.fields[1].name.0));
+        assert_ne!(state, successor);
+
+        successor.set("taxi", state.get_field("taxi").unwrap().clone());
+        assert_eq!(state, successor);
+        assert_eq!(format!("{:?}", State::new()), "{}");
+    }
+
+    #[test]
+    fn test_states_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<State>();
+
+        let mut state = State::new();
+        state.set("farm0", 600);
+        let other = std::thread::spawn(|| {
+            let mut state = State::new();
+            state.set("farm0", 600);
+            state
+        })
+        .join()
+        .unwrap();
+        assert!(Arc::ptr_eq(&state.fields[0].name.0, &other.fields[0].name.0));
+        assert_eq!(state, other);
+    }
+
+    #[test]
+    fn test_incremental_hash_matches_fresh_state() {
+        let mut state = State::new();
+        state.set("farm0", 600);
+        state.set("farm1", 1);
+        state.set("name", "farm");
+        state.update_int("farm0", |x| x - 4).unwrap();
+        state.update_with("farm1", |value| *value = Value::Int(3)).unwrap();
+
+        let mut fresh = State::new();
+        fresh.set("name", "farm");
+        fresh.set("farm1", 3);
+        fresh.set("farm0", 596);
+        assert_eq!(state.hash, fresh.hash);
+        assert_eq!(state, fresh);
+
+        fresh.set("farm1", 4);
+        assert_ne!(state.hash, fresh.hash);
+        fresh.set("farm1", 3);
+        assert_eq!(state.hash, fresh.hash);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::search::action::SearchAction;
+use crate::search::cost::Cost;
+use crate::problems::problem::Problem;
+
+// Outcome of replaying a plan from the initial state
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanValidation<S, C> {
+    pub first_inapplicable_step: Option<usize>, // Index of the first action that could not be applied
+    pub reaches_goal: bool,                     // Whether the last state reached is a goal state
+    pub cost: C,                                // Recomputed cost of the applied actions
+    pub final_state: S,
+}
+
+impl<S, C> PlanValidation<S, C> {
+    pub fn is_valid(&self) -> bool {
+        self.first_inapplicable_step.is_none() && self.reaches_goal
+    }
+}
+
+// Replay `plan` from `initial_state`, stopping at the first inapplicable action. Fails if the cost
+// of the applied actions overflows the cost type.
+pub fn validate_plan<P: Problem>(
+    problem: &P,
+    initial_state: &P::State,
+    plan: &[P::Action],
+) -> Result<PlanValidation<P::State, P::Cost>, &'static str> {
+    let mut state = initial_state.clone();
+    let mut cost = P::Cost::zero();
+    let mut first_inapplicable_step = None;
+
+    for (step, action) in plan.iter().enumerate() {
+        if !problem.is_applicable(&state, action) {
+            first_inapplicable_step = Some(step);
+            break;
+        }
+        state = problem.apply_action(&state, action);
+        cost = cost.checked_add(action.cost()).ok_or("Plan cost overflow")?;
+    }
+
+    Ok(PlanValidation {
+        first_inapplicable_step,
+        reaches_goal: problem.is_goal_state(&state),
+        cost,
+        final_state: state,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::farm_problem::farm_problem::FarmProblem;
+    use crate::search::action::Action;
+    use std::collections::HashMap;
+
+    fn transfer(name: &str, from: &str, to: &str) -> Action {
+        let mut parameters = HashMap::new();
+        parameters.insert("farm0".to_string(), crate::search::state::Value::Text(from.to_string()));
+        parameters.insert("farm1".to_string(), crate::search::state::Value::Text(to.to_string()));
+        Action::new(name.to_string(), 1, parameters)
+    }
+
+    #[test]
+    fn test_reports_first_inapplicable_step() {
+        let problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let state = problem.initial_state();
+        // farm1 starts with 1 unit: the slow move empties it, after which a fast move is impossible
+        let plan = vec![
+            transfer("move-slow", "farm1", "farm0"),
+            transfer("move-fast", "farm1", "farm0"),
+            transfer("move-fast", "farm0", "farm1"),
+        ];
+
+        let validation = validate_plan(&problem, &state, &plan).unwrap();
+
+        assert_eq!(validation.first_inapplicable_step, Some(1));
+        assert_eq!(validation.cost, 1);
+        assert!(!validation.reaches_goal);
+        assert!(!validation.is_valid());
+
+        let mut expensive = vec![transfer("move-slow", "farm0", "farm1"), transfer("move-slow", "farm0", "farm1")];
+        expensive[1].cost = i32::MAX;
+        assert_eq!(validate_plan(&problem, &state, &expensive).unwrap_err(), "Plan cost overflow");
+    }
+
+    #[test]
+    fn test_valid_plan() {
+        let problem = FarmProblem::from_json("inputs/farm_problem/small_input.json").unwrap();
+        let state = problem.initial_state();
+        // The weighted sum starts at 600 + 1.7 * 1 and each slow move from farm0 adds 0.7, so 341 reach 840
+        let plan: Vec<Action> = (0..341).map(|_| transfer("move-slow", "farm0", "farm1")).collect();
+
+        let validation = validate_plan(&problem, &state, &plan).unwrap();
+
+        assert_eq!(validation.first_inapplicable_step, None);
+        assert_eq!(validation.cost, 341);
+        assert!(validation.is_valid());
+
+        // The structured goal agrees with `is_goal_state`
+        let goal = problem.goal().unwrap();
+        assert!(goal.is_satisfied(&validation.final_state));
+        assert!(!goal.is_satisfied(&state));
+        assert_eq!(goal.satisfied_count(&state), goal.conditions.len() - 1);
+        assert_eq!(problem.goal_satisfaction(&state), Some(goal.satisfaction(&state)));
+        assert_eq!(problem.goal_satisfaction(&validation.final_state), Some(goal.satisfaction(&validation.final_state)));
+    }
+}